@@ -0,0 +1,9 @@
+#!/bin/sh
+# Downloads the corpora that `cargo bench` parses into this directory
+set -e
+
+cd "$(dirname "$0")"
+
+for name in twitter canada citm_catalog; do
+    curl -fsSL -o "$name.json" "https://raw.githubusercontent.com/miloyip/nativejson-benchmark/master/data/$name.json"
+done
@@ -0,0 +1,99 @@
+use std::fs;
+use std::path::Path;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+
+use rust_playground::{parse, parse_borrowed, EventReader, TokenizeOptions, Tokenizer};
+
+// The corpora of nativejson-benchmark, run `benches/data/download.sh` to get them
+const DOCUMENTS: &[&str] = &["twitter", "canada", "citm_catalog"];
+
+fn documents() -> Vec<(&'static str, String)> {
+    let data = Path::new(env!("CARGO_MANIFEST_DIR")).join("benches/data");
+
+    DOCUMENTS
+        .iter()
+        .map(|&name| {
+            let path = data.join(format!("{}.json", name));
+            let document = fs::read_to_string(&path).unwrap_or_else(|error| {
+                panic!(
+                    "Cannot read {}: {}, run benches/data/download.sh first",
+                    path.display(),
+                    error
+                )
+            });
+            (name, document)
+        })
+        .collect()
+}
+
+fn bench_tokenize(c: &mut Criterion) {
+    let mut group = c.benchmark_group("tokenize");
+    group.sample_size(10);
+
+    for (name, document) in documents() {
+        group.throughput(Throughput::Bytes(document.len() as u64));
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                for token in Tokenizer::new(black_box(&document), TokenizeOptions::default()) {
+                    black_box(token.unwrap());
+                }
+            })
+        });
+    }
+
+    group.finish();
+}
+
+fn bench_parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    group.sample_size(10);
+
+    for (name, document) in documents() {
+        group.throughput(Throughput::Bytes(document.len() as u64));
+        group.bench_function(name, |b| b.iter(|| parse(black_box(&document)).unwrap()));
+    }
+
+    group.finish();
+}
+
+fn bench_parse_borrowed(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse_borrowed");
+    group.sample_size(10);
+
+    for (name, document) in documents() {
+        group.throughput(Throughput::Bytes(document.len() as u64));
+        group.bench_function(name, |b| {
+            b.iter(|| parse_borrowed(black_box(&document)).unwrap())
+        });
+    }
+
+    group.finish();
+}
+
+fn bench_events(c: &mut Criterion) {
+    let mut group = c.benchmark_group("events");
+    group.sample_size(10);
+
+    for (name, document) in documents() {
+        group.throughput(Throughput::Bytes(document.len() as u64));
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                for event in EventReader::new(black_box(&document), TokenizeOptions::default()) {
+                    black_box(event.unwrap());
+                }
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_tokenize,
+    bench_parse,
+    bench_parse_borrowed,
+    bench_events
+);
+criterion_main!(benches);
@@ -0,0 +1,228 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use crate::error::Error;
+use crate::map::Map;
+use crate::number::Number;
+use crate::parser::{parse_tree, Json, ParseOptions, Tree};
+
+// Like `Json`, but strings without escape sequences point into the parsed input instead of being copied.
+// Objects are lists of entries in input order, duplicate keys are resolved by the parser.
+#[derive(Debug, PartialEq, Clone)]
+pub enum BorrowedJson<'a> {
+    Null,
+    Boolean(bool),
+    // The number exactly as it appears in the input
+    Number(&'a str),
+    String(Cow<'a, str>),
+    Array(Vec<BorrowedJson<'a>>),
+    Object(Vec<(Cow<'a, str>, BorrowedJson<'a>)>),
+}
+
+impl<'a> BorrowedJson<'a> {
+    // Copies all borrowed strings, so the result no longer depends on the input
+    pub fn into_owned(self) -> Json {
+        match self {
+            BorrowedJson::Null => Json::Null,
+            BorrowedJson::Boolean(value) => Json::Boolean(value),
+            BorrowedJson::Number(lexeme) => Json::Number(Number::from_lexeme(lexeme)),
+            BorrowedJson::String(value) => Json::String(value.into_owned()),
+            BorrowedJson::Array(values) => {
+                Json::Array(values.into_iter().map(BorrowedJson::into_owned).collect())
+            }
+            BorrowedJson::Object(entries) => {
+                let mut object = Map::with_capacity(entries.len());
+                for (key, value) in entries {
+                    object.insert(key.into_owned(), value.into_owned());
+                }
+                Json::Object(object)
+            }
+        }
+    }
+}
+
+// Small objects are searched linearly, larger ones get an index to keep parsing linear
+const INDEX_THRESHOLD: usize = 16;
+
+#[derive(Default)]
+pub(crate) struct BorrowedObject<'a> {
+    entries: Vec<(Cow<'a, str>, BorrowedJson<'a>)>,
+    // Position of each key in `entries`, once there are at least `INDEX_THRESHOLD` of them
+    indices: Option<HashMap<Cow<'a, str>, usize>>,
+}
+
+impl<'a> Tree<'a> for BorrowedJson<'a> {
+    type Object = BorrowedObject<'a>;
+
+    fn null() -> Self {
+        BorrowedJson::Null
+    }
+
+    fn boolean(value: bool) -> Self {
+        BorrowedJson::Boolean(value)
+    }
+
+    fn number(lexeme: &'a str) -> Self {
+        BorrowedJson::Number(lexeme)
+    }
+
+    fn string(value: Cow<'a, str>) -> Self {
+        BorrowedJson::String(value)
+    }
+
+    fn array(values: Vec<Self>) -> Self {
+        BorrowedJson::Array(values)
+    }
+
+    fn object(object: BorrowedObject<'a>) -> Self {
+        BorrowedJson::Object(object.entries)
+    }
+
+    fn get_mut<'o>(object: &'o mut BorrowedObject<'a>, key: &str) -> Option<&'o mut Self> {
+        let index = match &object.indices {
+            Some(indices) => *indices.get(key)?,
+            None => object.entries.iter().position(|(other, _)| other == key)?,
+        };
+
+        Some(&mut object.entries[index].1)
+    }
+
+    fn insert(object: &mut BorrowedObject<'a>, key: Cow<'a, str>, value: Self) {
+        if let Some(indices) = &mut object.indices {
+            indices.insert(key.clone(), object.entries.len());
+        }
+
+        object.entries.push((key, value));
+
+        if object.indices.is_none() && object.entries.len() >= INDEX_THRESHOLD {
+            let indices = object.entries.iter().enumerate();
+            object.indices = Some(
+                indices
+                    .map(|(index, (key, _))| (key.clone(), index))
+                    .collect(),
+            );
+        }
+    }
+
+    fn as_array_mut(&mut self) -> Option<&mut Vec<Self>> {
+        match self {
+            BorrowedJson::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+}
+
+pub fn parse(input: &str, options: ParseOptions) -> Result<BorrowedJson<'_>, Error> {
+    parse_tree(input, options)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use crate::borrowed::{parse, BorrowedJson};
+    use crate::map::Map;
+    use crate::parser::{DuplicateKeys, Json, ParseOptions};
+
+    #[test]
+    fn test_parse_borrows_strings() {
+        let input = r#"{"plain": "text", "escaped\n": "a\"b"}"#;
+        let json = parse(input, ParseOptions::default()).unwrap();
+
+        let BorrowedJson::Object(entries) = &json else {
+            panic!("Expected object, found {:?}", json);
+        };
+
+        assert!(matches!(entries[0].0, Cow::Borrowed("plain")));
+        assert!(matches!(
+            entries[0].1,
+            BorrowedJson::String(Cow::Borrowed("text"))
+        ));
+        assert!(matches!(&entries[1].0, Cow::Owned(key) if key == "escaped\n"));
+        assert!(
+            matches!(&entries[1].1, BorrowedJson::String(Cow::Owned(value)) if value == "a\"b")
+        );
+    }
+
+    #[test]
+    fn test_parse_borrows_numbers() {
+        let json = parse("[1.50, -2e3]", ParseOptions::default()).unwrap();
+
+        assert_eq!(
+            json,
+            BorrowedJson::Array(vec![
+                BorrowedJson::Number("1.50"),
+                BorrowedJson::Number("-2e3")
+            ])
+        );
+    }
+
+    #[test]
+    fn test_into_owned() {
+        let input = r#"{"a": [true, null, 1.0], "b": "é"}"#;
+        let json = parse(input, ParseOptions::default()).unwrap();
+
+        assert_eq!(
+            json.into_owned(),
+            Json::Object(Map::from([
+                (
+                    "a".to_owned(),
+                    Json::Array(vec![
+                        Json::Boolean(true),
+                        Json::Null,
+                        Json::Number(1.into())
+                    ]),
+                ),
+                ("b".to_owned(), Json::String("é".to_owned())),
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_parse_duplicate_keys() {
+        let input = r#"{"a": 1, "b": 2, "a": 3}"#;
+
+        let last_wins = parse(input, ParseOptions::default()).unwrap();
+        assert_eq!(
+            last_wins,
+            BorrowedJson::Object(vec![
+                ("a".into(), BorrowedJson::Number("3")),
+                ("b".into(), BorrowedJson::Number("2")),
+            ])
+        );
+
+        let options = ParseOptions {
+            duplicate_keys: DuplicateKeys::Error,
+            ..ParseOptions::default()
+        };
+        assert_eq!(
+            parse(input, options).unwrap_err().message(),
+            "Duplicate key `a` in object"
+        );
+    }
+
+    #[test]
+    fn test_parse_duplicate_keys_large_object() {
+        let entries: Vec<String> = (0..100)
+            .map(|index| format!("\"{}\": {}", index % 50, index))
+            .collect();
+        let input = format!("{{{}}}", entries.join(", "));
+
+        let options = ParseOptions {
+            duplicate_keys: DuplicateKeys::FirstWins,
+            ..ParseOptions::default()
+        };
+        let BorrowedJson::Object(entries) = parse(&input, options).unwrap() else {
+            panic!("Expected object");
+        };
+
+        assert_eq!(entries.len(), 50);
+        assert_eq!(entries[49], ("49".into(), BorrowedJson::Number("49")));
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let error = parse("[1, ]", ParseOptions::default()).unwrap_err();
+        assert_eq!(error.message(), "Found unexpected token `]`");
+    }
+}
@@ -0,0 +1,271 @@
+use std::fmt;
+
+use crate::error::Error;
+use crate::span::Span;
+use crate::tokenizer::{TokenType, TokenizeOptions, Tokenizer};
+
+#[derive(Debug, PartialEq)]
+pub struct Diagnostic<'a> {
+    pub input: &'a str,
+    // Where the input came from, like a file name, shown in front of the position
+    pub source: Option<&'a str>,
+    pub span: Span,
+    pub message: String,
+    pub help: Option<String>,
+}
+
+impl<'a> Diagnostic<'a> {
+    pub fn new(input: &'a str, span: Span, message: &str) -> Self {
+        Self {
+            input,
+            source: None,
+            span,
+            message: message.to_owned(),
+            help: find_help(input, span),
+        }
+    }
+
+    pub fn from_error(input: &'a str, error: &Error) -> Self {
+        let mut diagnostic = Self::new(input, error.span(), error.message());
+
+        if let Error::DuplicateKey(error) = error {
+            diagnostic.help = Some(format!("the key was first defined at {}", error.first));
+        }
+
+        diagnostic
+    }
+
+    fn line(&self) -> &'a str {
+        self.input.split('\n').nth(self.span.line - 1).unwrap_or("")
+    }
+}
+
+// Guesses what went wrong by looking at the tokens in front of the error
+fn find_help(input: &str, span: Span) -> Option<String> {
+    let current = input.get(span.offset..)?.chars().next();
+
+    if current == Some('\'') {
+        return Some("JSON strings must be enclosed in double quotes, like `\"text\"`".to_owned());
+    }
+
+    let options = TokenizeOptions { strict: false };
+    let tokenizer = Tokenizer::new(input.get(..span.offset)?, options);
+
+    let mut containers = vec![];
+    let mut significant = vec![];
+
+    for token in tokenizer {
+        let token = token.ok()?;
+
+        match token.type_ {
+            TokenType::Whitespace => continue,
+            TokenType::ArrayStart | TokenType::ObjectStart => containers.push(token.type_),
+            TokenType::ArrayEnd | TokenType::ObjectEnd => {
+                containers.pop();
+            }
+            _ => {}
+        }
+        significant.push(token.type_);
+    }
+
+    let in_object = containers.last() == Some(&TokenType::ObjectStart);
+    let previous = significant.last();
+    let expects_key = matches!(
+        previous,
+        Some(TokenType::ObjectStart) | Some(TokenType::Comma)
+    );
+
+    match current {
+        Some(']') | Some('}') if previous == Some(&TokenType::Comma) => {
+            Some("remove the trailing comma, JSON does not allow it".to_owned())
+        }
+        Some(c) if in_object && expects_key && (c.is_alphabetic() || c == '_') => {
+            Some("object keys must be double-quoted strings, like `\"key\"`".to_owned())
+        }
+        Some(c) if in_object && c != ':' && previous == Some(&TokenType::String) => {
+            let before_key = significant.iter().rev().nth(1);
+
+            if matches!(
+                before_key,
+                Some(TokenType::ObjectStart) | Some(TokenType::Comma)
+            ) {
+                Some("add a `:` between the key and its value".to_owned())
+            } else {
+                None
+            }
+        }
+        Some(c) if c.is_alphabetic() && !is_literal(&input[span.offset..]) => {
+            Some("JSON strings must be enclosed in double quotes, like `\"text\"`".to_owned())
+        }
+        _ => None,
+    }
+}
+
+fn is_literal(input: &str) -> bool {
+    let word: String = input.chars().take_while(|c| c.is_alphanumeric()).collect();
+    matches!(word.as_str(), "true" | "false" | "null")
+}
+
+impl<'a> fmt::Display for Diagnostic<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let line = self.line();
+        let line_number = self.span.line.to_string();
+        let gutter = " ".repeat(line_number.len());
+
+        // Keep tabs so the marker lines up with the source line
+        let indent: String = line
+            .chars()
+            .take(self.span.column - 1)
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+
+        let width = self
+            .input
+            .get(self.span.offset..self.span.offset + self.span.len)
+            .map(|token| token.split('\n').next().unwrap_or("").chars().count())
+            .unwrap_or(0)
+            .max(1);
+
+        writeln!(f, "error: {}", self.message)?;
+        match self.source {
+            Some(source) => writeln!(f, "{}--> {}:{}", gutter, source, self.span)?,
+            None => writeln!(f, "{}--> {}", gutter, self.span)?,
+        }
+        writeln!(f, "{} |", gutter)?;
+        writeln!(f, "{} | {}", line_number, line.trim_end_matches('\r'))?;
+        write!(f, "{} | {}^{}", gutter, indent, "~".repeat(width - 1))?;
+
+        if let Some(help) = &self.help {
+            writeln!(f)?;
+            writeln!(f, "{} |", gutter)?;
+            write!(f, "{} = help: {}", gutter, help)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::diagnostic::Diagnostic;
+    use crate::{parse, parse_with_options, DuplicateKeys, ParseOptions};
+
+    // Parses `input` and returns the diagnostic of the error
+    fn diagnose(input: &str) -> Diagnostic<'_> {
+        let error = parse(input).unwrap_err();
+        Diagnostic::from_error(input, &error)
+    }
+
+    macro_rules! help_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (input, expected): (&str, Option<&str>) = $value;
+                assert_eq!(diagnose(input).help.as_deref(), expected);
+            }
+        )*
+        }
+    }
+
+    help_tests! {
+        test_help_trailing_comma_array: ("[1, 2,]", Some("remove the trailing comma, JSON does not allow it")),
+        test_help_trailing_comma_object: ("{\"a\": 1, }", Some("remove the trailing comma, JSON does not allow it")),
+        test_help_missing_colon: ("{\"a\" 1}", Some("add a `:` between the key and its value")),
+        test_help_missing_colon_second_key: ("{\"a\": 1, \"b\" true}", Some("add a `:` between the key and its value")),
+        test_help_single_quotes: ("['a']", Some("JSON strings must be enclosed in double quotes, like `\"text\"`")),
+        test_help_unquoted_key: ("{foo: 1}", Some("object keys must be double-quoted strings, like `\"key\"`")),
+        test_help_unquoted_key_keyword: ("{\"a\": 1, null: 2}", Some("object keys must be double-quoted strings, like `\"key\"`")),
+        test_help_unquoted_string: ("[1, abc]", Some("JSON strings must be enclosed in double quotes, like `\"text\"`")),
+        test_help_missing_comma_array: ("[\"a\" 1]", None),
+        test_help_missing_comma_object: ("{\"a\": \"b\" \"c\": 1}", None),
+        test_help_end_of_input: ("[1, 2", None),
+        test_help_extra_literal: ("[1] null", None),
+    }
+
+    #[test]
+    fn test_render() {
+        let expected = [
+            "error: Unexpected token `1` in object",
+            " --> 2:9",
+            "  |",
+            "2 |   \"key\" 1",
+            "  |         ^",
+            "  |",
+            "  = help: add a `:` between the key and its value",
+        ];
+
+        assert_eq!(
+            diagnose("{\n  \"key\" 1\n}").to_string(),
+            expected.join("\n")
+        );
+    }
+
+    #[test]
+    fn test_render_source() {
+        let expected = [
+            "error: Unexpected extra input found",
+            " --> config.json:1:6",
+            "  |",
+            "1 | true false",
+            "  |      ^~~~~",
+        ];
+        let diagnostic = Diagnostic {
+            source: Some("config.json"),
+            ..diagnose("true false")
+        };
+
+        assert_eq!(diagnostic.to_string(), expected.join("\n"));
+    }
+
+    #[test]
+    fn test_render_underline() {
+        let expected = [
+            "error: Unexpected extra input found",
+            " --> 1:6",
+            "  |",
+            "1 | true false",
+            "  |      ^~~~~",
+        ];
+
+        assert_eq!(diagnose("true false").to_string(), expected.join("\n"));
+    }
+
+    #[test]
+    fn test_render_duplicate_key() {
+        let input = "{\"id\": 1, \"id\": 2}";
+        let options = ParseOptions {
+            duplicate_keys: DuplicateKeys::Error,
+            ..ParseOptions::default()
+        };
+        let error = parse_with_options(input, options).unwrap_err();
+
+        let expected = [
+            "error: Duplicate key `id` in object",
+            " --> 1:11",
+            "  |",
+            "1 | {\"id\": 1, \"id\": 2}",
+            "  |           ^~~~",
+            "  |",
+            "  = help: the key was first defined at 1:2",
+        ];
+
+        assert_eq!(
+            Diagnostic::from_error(input, &error).to_string(),
+            expected.join("\n")
+        );
+    }
+
+    #[test]
+    fn test_render_end_of_input() {
+        let expected = [
+            "error: Unexpected end of input",
+            " --> 1:5",
+            "  |",
+            "1 | [1, ",
+            "  |     ^",
+        ];
+
+        assert_eq!(diagnose("[1, ").to_string(), expected.join("\n"));
+    }
+}
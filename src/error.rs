@@ -0,0 +1,101 @@
+use std::fmt;
+
+use crate::parser::{DuplicateKeyError, ParseError};
+use crate::reader::ReadError;
+use crate::span::Span;
+use crate::tokenizer::TokenizeError;
+
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    Tokenize(TokenizeError),
+    Parse(ParseError),
+    DuplicateKey(DuplicateKeyError),
+    Read(ReadError),
+}
+
+impl Error {
+    pub fn span(&self) -> Span {
+        match self {
+            Error::Tokenize(error) => error.span,
+            Error::Parse(error) => error.span,
+            Error::DuplicateKey(error) => error.span,
+            Error::Read(error) => error.span,
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            Error::Tokenize(error) => &error.message,
+            Error::Parse(error) => &error.message,
+            Error::DuplicateKey(error) => &error.message,
+            Error::Read(error) => &error.message,
+        }
+    }
+
+    // Moves the spans of an error in a part of a larger input, see `Span::shift`
+    pub(crate) fn shift(self, start: Span) -> Self {
+        match self {
+            Error::Tokenize(error) => {
+                Error::Tokenize(TokenizeError::new(error.span.shift(start), error.message))
+            }
+            Error::Parse(error) => {
+                Error::Parse(ParseError::new(error.span.shift(start), error.message))
+            }
+            Error::DuplicateKey(error) => Error::DuplicateKey(DuplicateKeyError {
+                span: error.span.shift(start),
+                first: error.first.shift(start),
+                message: error.message,
+            }),
+            Error::Read(error) => Error::Read(ReadError {
+                span: error.span.shift(start),
+                ..error
+            }),
+        }
+    }
+}
+
+impl From<TokenizeError> for Error {
+    fn from(error: TokenizeError) -> Self {
+        Error::Tokenize(error)
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(error: ParseError) -> Self {
+        Error::Parse(error)
+    }
+}
+
+impl From<DuplicateKeyError> for Error {
+    fn from(error: DuplicateKeyError) -> Self {
+        Error::DuplicateKey(error)
+    }
+}
+
+impl From<ReadError> for Error {
+    fn from(error: ReadError) -> Self {
+        Error::Read(error)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Tokenize(error) => error.fmt(f),
+            Error::Parse(error) => error.fmt(f),
+            Error::DuplicateKey(error) => error.fmt(f),
+            Error::Read(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Tokenize(error) => Some(error),
+            Error::Parse(error) => Some(error),
+            Error::DuplicateKey(error) => Some(error),
+            Error::Read(error) => Some(error),
+        }
+    }
+}
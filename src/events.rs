@@ -0,0 +1,427 @@
+use std::borrow::Cow;
+
+use crate::borrowed::BorrowedJson;
+use crate::error::Error;
+use crate::parser::ParseError;
+use crate::span::Span;
+use crate::tokenizer::{unescape, Token, TokenType, TokenizeOptions, Tokenizer};
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Event<'a> {
+    StartObject,
+    Key(Cow<'a, str>),
+    EndObject,
+    StartArray,
+    // A string, number, boolean or null
+    Value(BorrowedJson<'a>),
+    EndArray,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Container {
+    Array,
+    Object,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum State {
+    // Expecting a value, or the end of the array if it is the `first` one
+    Value { first: bool },
+    // Expecting a key, or the end of the object if it is the `first` one
+    Key { first: bool },
+    Colon,
+    // Expecting `,` or the end of the innermost container
+    AfterValue,
+    // Expecting the end of input, or another value if they are concatenated
+    End,
+    // Nothing left to read, after the end of input or an error
+    Done,
+}
+
+// Reads `input` as a stream of events, without building a tree.
+// The input itself must be in memory as a whole, because events borrow from it.
+// Only what is kept besides the input is constant: it grows with the nesting depth, not with the size of the input.
+pub struct EventReader<'a> {
+    input: &'a str,
+    tokenizer: Tokenizer<'a>,
+    // Next significant token, if it was peeked at but not consumed yet
+    token: Option<Token<'a>>,
+    containers: Vec<Container>,
+    state: State,
+    // Offset and length of the token of the last event
+    position: (usize, usize),
+    // Reads any number of values that follow each other, instead of exactly one
+    concatenated: bool,
+}
+
+impl<'a> EventReader<'a> {
+    pub fn new(input: &'a str, options: TokenizeOptions) -> Self {
+        Self {
+            input,
+            tokenizer: Tokenizer::new(input, options),
+            token: None,
+            containers: vec![],
+            state: State::Value { first: false },
+            position: (0, 0),
+            concatenated: false,
+        }
+    }
+
+    // Reads values that follow each other, like `{"a": 1} {"b": 2}`, empty input has no events
+    pub fn concatenated(input: &'a str, options: TokenizeOptions) -> Self {
+        Self {
+            state: State::End,
+            concatenated: true,
+            ..Self::new(input, options)
+        }
+    }
+
+    // Span of the token that produced the last event
+    pub fn span(&self) -> Span {
+        Span::new(self.input, self.position.0, self.position.1)
+    }
+
+    pub(crate) fn input(&self) -> &'a str {
+        self.input
+    }
+
+    pub(crate) fn position(&self) -> (usize, usize) {
+        self.position
+    }
+
+    // Returns the next token that is not whitespace, without consuming it
+    fn peek(&mut self) -> Result<Option<Token<'a>>, Error> {
+        if self.token.is_none() {
+            self.tokenizer.skip_whitespace();
+            self.token = self.tokenizer.next().transpose()?;
+        }
+
+        Ok(self.token)
+    }
+
+    // Consumes the peeked token, it becomes the position of the next event
+    fn advance(&mut self) {
+        if let Some(token) = self.token.take() {
+            self.position = (token.offset, token.len());
+        }
+    }
+
+    // Creates an error pointing at the peeked token, or at the end of input if there is none
+    fn error(&self, message: String) -> Error {
+        let span = match &self.token {
+            None => Span::new(self.input, self.input.len(), 0),
+            Some(token) => Span::new(self.input, token.offset, token.len()),
+        };
+
+        Error::Parse(ParseError::new(span, message))
+    }
+
+    // Returns the next significant token, which must exist
+    #[inline]
+    fn expect_token(&mut self) -> Result<Token<'a>, Error> {
+        if let Some(token) = self.token {
+            return Ok(token);
+        }
+
+        self.tokenizer.skip_whitespace();
+        match self.tokenizer.next() {
+            Some(Ok(token)) => {
+                self.token = Some(token);
+                Ok(token)
+            }
+            Some(Err(error)) => Err(error.into()),
+            None => Err(self.error("Unexpected end of input".to_owned())),
+        }
+    }
+
+    // Picks the state after a complete value, which depends on what contains it
+    fn end_value(&mut self) {
+        self.state = match self.containers.last() {
+            None => State::End,
+            Some(_) => State::AfterValue,
+        };
+    }
+
+    fn end_container(&mut self, event: Event<'a>) -> Result<Option<Event<'a>>, Error> {
+        self.advance();
+        self.containers.pop();
+        self.end_value();
+
+        Ok(Some(event))
+    }
+
+    // Returns `None` after the top level value, once the rest of the input is found to be empty
+    #[inline]
+    pub fn next_event(&mut self) -> Result<Option<Event<'a>>, Error> {
+        loop {
+            match self.state {
+                State::Done => return Ok(None),
+                State::End => {
+                    if self.peek()?.is_some() {
+                        if self.concatenated {
+                            self.state = State::Value { first: false };
+                            continue;
+                        }
+
+                        return Err(self.error("Unexpected extra input found".to_owned()));
+                    }
+
+                    self.state = State::Done;
+                    return Ok(None);
+                }
+                State::Value { first } => {
+                    let token = self.expect_token()?;
+
+                    if first && token.type_ == TokenType::ArrayEnd {
+                        return self.end_container(Event::EndArray);
+                    }
+
+                    return self.value(token);
+                }
+                State::Key { first } => {
+                    let token = self.expect_token()?;
+
+                    if first && token.type_ == TokenType::ObjectEnd {
+                        return self.end_container(Event::EndObject);
+                    }
+
+                    if token.type_ != TokenType::String {
+                        let message = format!("Cannot parse `{}` as string", token.value);
+                        return Err(self.error(message));
+                    }
+
+                    // Strictness is enforced by the tokenizer, so decode leniently here
+                    let key = unescape(self.input, &token, false)?;
+
+                    self.advance();
+                    self.state = State::Colon;
+                    return Ok(Some(Event::Key(key)));
+                }
+                State::Colon => {
+                    let token = self.expect_token()?;
+
+                    if token.type_ != TokenType::Colon {
+                        let message = format!("Unexpected token `{}` in object", token.value);
+                        return Err(self.error(message));
+                    }
+
+                    self.advance();
+                    self.state = State::Value { first: false };
+                }
+                State::AfterValue => {
+                    let token = self.expect_token()?;
+
+                    match (self.containers.last(), &token.type_) {
+                        (Some(Container::Array), TokenType::Comma) => {
+                            self.advance();
+                            self.state = State::Value { first: false };
+                        }
+                        (Some(Container::Array), TokenType::ArrayEnd) => {
+                            return self.end_container(Event::EndArray);
+                        }
+                        (Some(Container::Object), TokenType::Comma) => {
+                            self.advance();
+                            self.state = State::Key { first: false };
+                        }
+                        (Some(Container::Object), TokenType::ObjectEnd) => {
+                            return self.end_container(Event::EndObject);
+                        }
+                        (Some(Container::Array), _) => {
+                            let message = format!("Unexpected token `{}` in array", token.value);
+                            return Err(self.error(message));
+                        }
+                        _ => {
+                            let message = format!("Unexpected token `{}` in object", token.value);
+                            return Err(self.error(message));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn value(&mut self, token: Token<'a>) -> Result<Option<Event<'a>>, Error> {
+        let value = match token.type_ {
+            TokenType::Null => BorrowedJson::Null,
+            TokenType::True => BorrowedJson::Boolean(true),
+            TokenType::False => BorrowedJson::Boolean(false),
+            TokenType::Number => BorrowedJson::Number(token.value),
+            TokenType::String => BorrowedJson::String(unescape(self.input, &token, false)?),
+            TokenType::ArrayStart => {
+                self.advance();
+                self.containers.push(Container::Array);
+                self.state = State::Value { first: true };
+                return Ok(Some(Event::StartArray));
+            }
+            TokenType::ObjectStart => {
+                self.advance();
+                self.containers.push(Container::Object);
+                self.state = State::Key { first: true };
+                return Ok(Some(Event::StartObject));
+            }
+            _ => {
+                let message = format!("Found unexpected token `{}`", token.value);
+                return Err(self.error(message));
+            }
+        };
+
+        self.advance();
+        self.end_value();
+        Ok(Some(Event::Value(value)))
+    }
+}
+
+impl<'a> Iterator for EventReader<'a> {
+    type Item = Result<Event<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let event = self.next_event();
+
+        // Stop after the first error
+        if event.is_err() {
+            self.state = State::Done;
+        }
+
+        event.transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::borrowed::BorrowedJson;
+    use crate::error::Error;
+    use crate::events::{Event, EventReader};
+    use crate::parser::ParseError;
+    use crate::span::Span;
+    use crate::tokenizer::TokenizeOptions;
+
+    fn events(input: &str) -> Result<Vec<Event<'_>>, Error> {
+        EventReader::new(input, TokenizeOptions::default()).collect()
+    }
+
+    macro_rules! event_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (input, expected) = $value;
+                assert_eq!(events(input), expected);
+            }
+        )*
+        }
+    }
+
+    event_tests! {
+        test_events_scalar: ("1.5", Ok(vec![Event::Value(BorrowedJson::Number("1.5"))])),
+        test_events_empty_array: ("[]", Ok(vec![Event::StartArray, Event::EndArray])),
+        test_events_empty_object: ("{}", Ok(vec![Event::StartObject, Event::EndObject])),
+        test_events_nested: (
+                "{\"a\": [true, {\"b\": null}], \"c\": \"d\"}",
+                Ok(vec![
+                    Event::StartObject,
+                    Event::Key("a".into()),
+                    Event::StartArray,
+                    Event::Value(BorrowedJson::Boolean(true)),
+                    Event::StartObject,
+                    Event::Key("b".into()),
+                    Event::Value(BorrowedJson::Null),
+                    Event::EndObject,
+                    Event::EndArray,
+                    Event::Key("c".into()),
+                    Event::Value(BorrowedJson::String("d".into())),
+                    Event::EndObject,
+                ]),
+            ),
+        test_events_escaped_key: (
+                "{\"a\\nb\": 1}",
+                Ok(vec![
+                    Event::StartObject,
+                    Event::Key("a\nb".into()),
+                    Event::Value(BorrowedJson::Number("1")),
+                    Event::EndObject,
+                ]),
+            ),
+        test_events_trailing_comma: (
+                "[1,]",
+                Err(Error::Parse(ParseError {
+                    span: Span { offset: 3, len: 1, line: 1, column: 4 },
+                    message: "Found unexpected token `]`".to_owned(),
+                })),
+            ),
+        test_events_extra_input: (
+                "{} []",
+                Err(Error::Parse(ParseError {
+                    span: Span { offset: 3, len: 1, line: 1, column: 4 },
+                    message: "Unexpected extra input found".to_owned(),
+                })),
+            ),
+        test_events_mismatched_end: (
+                "[1}",
+                Err(Error::Parse(ParseError {
+                    span: Span { offset: 2, len: 1, line: 1, column: 3 },
+                    message: "Unexpected token `}` in array".to_owned(),
+                })),
+            ),
+    }
+
+    #[test]
+    fn test_events_before_error() {
+        let mut reader = EventReader::new("[1, x]", TokenizeOptions::default());
+
+        assert_eq!(reader.next(), Some(Ok(Event::StartArray)));
+        assert_eq!(
+            reader.next(),
+            Some(Ok(Event::Value(BorrowedJson::Number("1"))))
+        );
+        assert_eq!(
+            reader.next().unwrap().unwrap_err().message(),
+            "Unhandled character"
+        );
+        assert_eq!(reader.next(), None);
+    }
+
+    #[test]
+    fn test_events_span() {
+        let mut reader = EventReader::new("{\n  \"key\": 1\n}", TokenizeOptions::default());
+
+        reader.next();
+        assert_eq!(reader.next(), Some(Ok(Event::Key("key".into()))));
+        assert_eq!(
+            reader.span(),
+            Span {
+                offset: 4,
+                len: 5,
+                line: 2,
+                column: 3
+            }
+        );
+    }
+
+    #[test]
+    fn test_events_concatenated() {
+        let reader = EventReader::concatenated("1{}\n[]", TokenizeOptions::default());
+
+        assert_eq!(
+            reader.collect::<Result<Vec<_>, _>>(),
+            Ok(vec![
+                Event::Value(BorrowedJson::Number("1")),
+                Event::StartObject,
+                Event::EndObject,
+                Event::StartArray,
+                Event::EndArray,
+            ])
+        );
+
+        let mut empty = EventReader::concatenated(" \n", TokenizeOptions::default());
+        assert_eq!(empty.next(), None);
+    }
+
+    #[test]
+    fn test_events_deep_nesting() {
+        let depth = 10000;
+        let input = format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+        let reader = EventReader::new(&input, TokenizeOptions::default());
+
+        assert_eq!(reader.map(Result::unwrap).count(), depth * 2);
+    }
+}
@@ -0,0 +1,951 @@
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::fmt;
+
+use crate::map::Map;
+use crate::number::Number;
+use crate::parser::{parse, Json, ParseOptions};
+use crate::pointer::type_name;
+use crate::span::Span;
+
+// A filter that can't be parsed, or that fails on a value. The span points into the filter.
+#[derive(Debug, PartialEq)]
+pub struct FilterError {
+    pub span: Span,
+    pub message: String,
+}
+
+impl FilterError {
+    fn new(span: Span, message: String) -> Self {
+        Self { span, message }
+    }
+}
+
+impl fmt::Display for FilterError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.span, self.message)
+    }
+}
+
+impl std::error::Error for FilterError {}
+
+// A filter in a subset of the jq language, like `.items[] | select(.price < 10) | {name}`.
+// Supported are paths (`.foo`, `.[0]`, `.[]`), pipes, `,`, comparisons, `and`, `or`,
+// array and object construction, and the functions `select`, `map`, `keys`, `length`, `not` and `empty`.
+#[derive(Debug)]
+pub struct Filter {
+    expression: Expression,
+}
+
+impl Filter {
+    pub fn parse(filter: &str) -> Result<Self, FilterError> {
+        let mut parser = FilterParser { filter, offset: 0 };
+
+        parser.skip_blank();
+        let expression = parser.parse_pipe()?;
+        parser.skip_blank();
+
+        if parser.offset < filter.len() {
+            return parser.unexpected("`|` or the end of the filter");
+        }
+
+        Ok(Self { expression })
+    }
+
+    // Returns every output of the filter, which can be any number of values like in jq
+    pub fn apply(&self, json: &Json) -> Result<Vec<Json>, FilterError> {
+        Ok(into_owned(self.expression.evaluate(json)?))
+    }
+}
+
+#[derive(Debug)]
+struct Expression {
+    kind: Kind,
+    // Where the expression is in the filter, for errors while evaluating it
+    span: Span,
+}
+
+#[derive(Debug)]
+enum Kind {
+    Identity,
+    Field(Box<Expression>, String),
+    Index(Box<Expression>, Box<Expression>),
+    Iterate(Box<Expression>),
+    Literal(Json),
+    Array(Option<Box<Expression>>),
+    // Pairs of key and value
+    Object(Vec<(Expression, Expression)>),
+    Pipe(Box<Expression>, Box<Expression>),
+    Comma(Box<Expression>, Box<Expression>),
+    Compare(Box<Expression>, Comparison, Box<Expression>),
+    And(Box<Expression>, Box<Expression>),
+    Or(Box<Expression>, Box<Expression>),
+    Select(Box<Expression>),
+    Map(Box<Expression>),
+    Keys,
+    Length,
+    Not,
+    Empty,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+impl Comparison {
+    fn test(self, order: Ordering) -> bool {
+        match self {
+            Comparison::Equal => order.is_eq(),
+            Comparison::NotEqual => order.is_ne(),
+            Comparison::Less => order.is_lt(),
+            Comparison::LessOrEqual => order.is_le(),
+            Comparison::Greater => order.is_gt(),
+            Comparison::GreaterOrEqual => order.is_ge(),
+        }
+    }
+}
+
+impl Expression {
+    // Values that are selected from the input are borrowed, only values that are built are owned
+    fn evaluate<'a>(&'a self, input: &'a Json) -> Result<Vec<Cow<'a, Json>>, FilterError> {
+        let outputs = match &self.kind {
+            Kind::Identity => vec![Cow::Borrowed(input)],
+            Kind::Field(target, name) => {
+                let mut outputs = vec![];
+                for value in target.evaluate(input)? {
+                    outputs.push(self.index(&value, &Json::String(name.clone()))?);
+                }
+                outputs
+            }
+            // Like jq, the index is the outer loop, so `.[0, 1]` gives the first of every target first
+            Kind::Index(target, index) => {
+                let targets = target.evaluate(input)?;
+                let mut outputs = vec![];
+                for index in index.evaluate(input)? {
+                    for value in &targets {
+                        outputs.push(self.index(value, &index)?);
+                    }
+                }
+                outputs
+            }
+            Kind::Iterate(target) => {
+                let mut outputs = vec![];
+                for value in target.evaluate(input)? {
+                    outputs.extend(self.iterate(value)?);
+                }
+                outputs
+            }
+            Kind::Literal(json) => vec![Cow::Borrowed(json)],
+            Kind::Array(None) => vec![Cow::Owned(Json::Array(vec![]))],
+            Kind::Array(Some(elements)) => {
+                let elements = elements.evaluate(input)?;
+                vec![Cow::Owned(Json::Array(into_owned(elements)))]
+            }
+            Kind::Object(entries) => evaluate_object(entries, input)?
+                .into_iter()
+                .map(Cow::Owned)
+                .collect(),
+            Kind::Pipe(left, right) => {
+                let mut outputs = vec![];
+                for value in left.evaluate(input)? {
+                    match value {
+                        Cow::Borrowed(value) => outputs.extend(right.evaluate(value)?),
+                        // Outputs of `right` may borrow from `value`, which is dropped here
+                        Cow::Owned(value) => {
+                            let values = into_owned(right.evaluate(&value)?);
+                            outputs.extend(values.into_iter().map(Cow::Owned));
+                        }
+                    }
+                }
+                outputs
+            }
+            Kind::Comma(left, right) => {
+                let mut outputs = left.evaluate(input)?;
+                outputs.extend(right.evaluate(input)?);
+                outputs
+            }
+            // Like jq, the right operand is the outer loop
+            Kind::Compare(left, comparison, right) => {
+                let left = left.evaluate(input)?;
+                let mut outputs = vec![];
+                for right in right.evaluate(input)? {
+                    for left in &left {
+                        let result = comparison.test(order(left, &right));
+                        outputs.push(Cow::Owned(Json::Boolean(result)));
+                    }
+                }
+                outputs
+            }
+            Kind::And(left, right) => {
+                let mut outputs = vec![];
+                for left in left.evaluate(input)? {
+                    if !truthy(&left) {
+                        outputs.push(Cow::Owned(Json::Boolean(false)));
+                        continue;
+                    }
+                    for right in right.evaluate(input)? {
+                        outputs.push(Cow::Owned(Json::Boolean(truthy(&right))));
+                    }
+                }
+                outputs
+            }
+            Kind::Or(left, right) => {
+                let mut outputs = vec![];
+                for left in left.evaluate(input)? {
+                    if truthy(&left) {
+                        outputs.push(Cow::Owned(Json::Boolean(true)));
+                        continue;
+                    }
+                    for right in right.evaluate(input)? {
+                        outputs.push(Cow::Owned(Json::Boolean(truthy(&right))));
+                    }
+                }
+                outputs
+            }
+            Kind::Select(condition) => {
+                let mut outputs = vec![];
+                for result in condition.evaluate(input)? {
+                    if truthy(&result) {
+                        outputs.push(Cow::Borrowed(input));
+                    }
+                }
+                outputs
+            }
+            Kind::Map(function) => {
+                let mut mapped = vec![];
+                for value in self.iterate(Cow::Borrowed(input))? {
+                    mapped.extend(into_owned(function.evaluate(&value)?));
+                }
+                vec![Cow::Owned(Json::Array(mapped))]
+            }
+            Kind::Keys => vec![Cow::Owned(self.keys(input)?)],
+            Kind::Length => vec![Cow::Owned(self.length(input)?)],
+            Kind::Not => vec![Cow::Owned(Json::Boolean(!truthy(input)))],
+            Kind::Empty => vec![],
+        };
+
+        Ok(outputs)
+    }
+
+    fn error<T>(&self, message: String) -> Result<T, FilterError> {
+        Err(FilterError::new(self.span, message))
+    }
+
+    // Like jq, looking up anything in `null` and missing keys or indices give `null`
+    fn index<'a>(&self, value: &Cow<'a, Json>, index: &Json) -> Result<Cow<'a, Json>, FilterError> {
+        let element = match value {
+            Cow::Borrowed(value) => self.lookup(value, index)?.map(Cow::Borrowed),
+            Cow::Owned(value) => self.lookup(value, index)?.cloned().map(Cow::Owned),
+        };
+
+        Ok(element.unwrap_or(Cow::Owned(Json::Null)))
+    }
+
+    fn lookup<'a>(&self, value: &'a Json, index: &Json) -> Result<Option<&'a Json>, FilterError> {
+        match (value, index) {
+            (Json::Null, Json::String(_) | Json::Number(_)) => Ok(None),
+            (Json::Object(map), Json::String(key)) => Ok(map.get(key)),
+            (Json::Array(array), Json::Number(number)) => {
+                let Some(index) = number.as_f64() else {
+                    return Ok(None);
+                };
+                let index = index.floor() as i64;
+                let index = if index < 0 {
+                    array.len() as i64 + index
+                } else {
+                    index
+                };
+
+                Ok(usize::try_from(index)
+                    .ok()
+                    .and_then(|index| array.get(index)))
+            }
+            (_, Json::String(key)) => {
+                self.error(format!("Cannot look up `{}` in {}", key, type_name(value)))
+            }
+            _ => self.error(format!(
+                "Cannot index {} with {}",
+                type_name(value),
+                type_name(index)
+            )),
+        }
+    }
+
+    fn iterate<'a>(&self, value: Cow<'a, Json>) -> Result<Vec<Cow<'a, Json>>, FilterError> {
+        match value {
+            Cow::Borrowed(Json::Array(array)) => Ok(array.iter().map(Cow::Borrowed).collect()),
+            Cow::Borrowed(Json::Object(map)) => {
+                Ok(map.iter().map(|(_, value)| Cow::Borrowed(value)).collect())
+            }
+            Cow::Owned(Json::Array(array)) => Ok(array.into_iter().map(Cow::Owned).collect()),
+            Cow::Owned(Json::Object(map)) => Ok(map
+                .into_iter()
+                .map(|(_, value)| Cow::Owned(value))
+                .collect()),
+            _ => self.error(format!("Cannot iterate over {}", type_name(&value))),
+        }
+    }
+
+    // Keys of an object sorted by code point, or the indices of an array
+    fn keys(&self, value: &Json) -> Result<Json, FilterError> {
+        match value {
+            Json::Object(map) => {
+                let mut keys: Vec<_> = map.keys().cloned().collect();
+                keys.sort();
+                Ok(Json::Array(keys.into_iter().map(Json::String).collect()))
+            }
+            Json::Array(array) => Ok(Json::Array(
+                (0..array.len())
+                    .map(|index| Json::Number(index.into()))
+                    .collect(),
+            )),
+            _ => self.error(format!("{} has no keys", type_name(value))),
+        }
+    }
+
+    fn length(&self, value: &Json) -> Result<Json, FilterError> {
+        let len = match value {
+            Json::Null => 0,
+            // The length of a number is its absolute value
+            Json::Number(number) => {
+                let absolute = number.as_str().trim_start_matches('-');
+                return Ok(Json::Number(absolute.parse().unwrap()));
+            }
+            Json::String(string) => string.chars().count(),
+            Json::Array(array) => array.len(),
+            Json::Object(map) => map.len(),
+            Json::Boolean(_) => return self.error("boolean has no length".to_owned()),
+        };
+
+        Ok(Json::Number(len.into()))
+    }
+}
+
+fn into_owned(values: Vec<Cow<Json>>) -> Vec<Json> {
+    values.into_iter().map(Cow::into_owned).collect()
+}
+
+// Builds an object for every combination of keys and values, when they have more than one output
+fn evaluate_object(
+    entries: &[(Expression, Expression)],
+    input: &Json,
+) -> Result<Vec<Json>, FilterError> {
+    let mut objects = vec![Map::new()];
+
+    for (key, value) in entries {
+        let keys = key.evaluate(input)?;
+        let values = value.evaluate(input)?;
+        let mut combined = vec![];
+
+        for object in &objects {
+            for name in &keys {
+                let Json::String(name) = name.as_ref() else {
+                    let message = format!("Object keys must be strings, found {}", type_name(name));
+                    return key.error(message);
+                };
+
+                for value in &values {
+                    let mut object = object.clone();
+                    object.insert(name.clone(), value.as_ref().clone());
+                    combined.push(object);
+                }
+            }
+        }
+
+        objects = combined;
+    }
+
+    Ok(objects.into_iter().map(Json::Object).collect())
+}
+
+// Only `false` and `null` are false, like in jq
+fn truthy(json: &Json) -> bool {
+    !matches!(json, Json::Null | Json::Boolean(false))
+}
+
+// Orders any two values like jq: null < false < true < numbers < strings < arrays < objects.
+// Objects are compared by their sorted keys first, then by the values of those keys.
+fn order(left: &Json, right: &Json) -> Ordering {
+    match (left, right) {
+        (Json::Number(left), Json::Number(right)) => left.as_decimal().cmp(&right.as_decimal()),
+        (Json::String(left), Json::String(right)) => left.cmp(right),
+        (Json::Array(left), Json::Array(right)) => left
+            .iter()
+            .zip(right)
+            .map(|(left, right)| order(left, right))
+            .find(|order| order.is_ne())
+            .unwrap_or_else(|| left.len().cmp(&right.len())),
+        (Json::Object(left), Json::Object(right)) => {
+            let mut left_keys: Vec<_> = left.keys().collect();
+            let mut right_keys: Vec<_> = right.keys().collect();
+            left_keys.sort();
+            right_keys.sort();
+
+            left_keys.cmp(&right_keys).then_with(|| {
+                left_keys
+                    .iter()
+                    .map(|key| order(left.get(key).unwrap(), right.get(key).unwrap()))
+                    .find(|order| order.is_ne())
+                    .unwrap_or(Ordering::Equal)
+            })
+        }
+        _ => rank(left).cmp(&rank(right)),
+    }
+}
+
+fn rank(json: &Json) -> u8 {
+    match json {
+        Json::Null => 0,
+        Json::Boolean(false) => 1,
+        Json::Boolean(true) => 2,
+        Json::Number(_) => 3,
+        Json::String(_) => 4,
+        Json::Array(_) => 5,
+        Json::Object(_) => 6,
+    }
+}
+
+struct FilterParser<'f> {
+    filter: &'f str,
+    offset: usize,
+}
+
+impl<'f> FilterParser<'f> {
+    fn rest(&self) -> &'f str {
+        &self.filter[self.offset..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn eat(&mut self, text: &str) -> bool {
+        let found = self.rest().starts_with(text);
+        if found {
+            self.offset += text.len();
+        }
+        found
+    }
+
+    fn skip_blank(&mut self) {
+        let rest = self.rest();
+        self.offset += rest.len() - rest.trim_start().len();
+    }
+
+    // Length of the identifier at the start of `text`, 0 if there is none
+    fn identifier_len(text: &str) -> usize {
+        match text.chars().next() {
+            Some(c) if c.is_ascii_alphabetic() || c == '_' => text
+                .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                .unwrap_or(text.len()),
+            _ => 0,
+        }
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let len = Self::identifier_len(self.rest());
+        let found = &self.rest()[..len] == keyword;
+        if found {
+            self.offset += len;
+        }
+        found
+    }
+
+    fn span(&self, start: usize) -> Span {
+        Span::new(self.filter, start, self.offset - start)
+    }
+
+    fn expression(&self, kind: Kind, start: usize) -> Expression {
+        Expression {
+            kind,
+            span: self.span(start),
+        }
+    }
+
+    fn error<T>(&self, start: usize, message: String) -> Result<T, FilterError> {
+        Err(FilterError::new(self.span(start), message))
+    }
+
+    fn unexpected<T>(&self, expected: &str) -> Result<T, FilterError> {
+        let (len, found) = match self.peek() {
+            Some(c) => (c.len_utf8(), format!("`{}`", c)),
+            None => (0, "end of filter".to_owned()),
+        };
+        let message = format!("Unexpected {}, expected {}", found, expected);
+
+        Err(FilterError::new(
+            Span::new(self.filter, self.offset, len),
+            message,
+        ))
+    }
+
+    fn expect(&mut self, text: &str) -> Result<(), FilterError> {
+        if !self.eat(text) {
+            return self.unexpected(&format!("`{}`", text));
+        }
+        Ok(())
+    }
+
+    // Binary operators from the loosest to the tightest: `|`, `,`, `or`, `and`, comparisons
+    fn parse_pipe(&mut self) -> Result<Expression, FilterError> {
+        let start = self.offset;
+        let left = self.parse_comma()?;
+        self.skip_blank();
+
+        if !self.eat("|") {
+            return Ok(left);
+        }
+
+        self.skip_blank();
+        let right = self.parse_pipe()?;
+        Ok(self.expression(Kind::Pipe(Box::new(left), Box::new(right)), start))
+    }
+
+    fn parse_comma(&mut self) -> Result<Expression, FilterError> {
+        let start = self.offset;
+        let mut left = self.parse_or()?;
+
+        loop {
+            self.skip_blank();
+            if !self.eat(",") {
+                return Ok(left);
+            }
+
+            self.skip_blank();
+            let right = self.parse_or()?;
+            left = self.expression(Kind::Comma(Box::new(left), Box::new(right)), start);
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Expression, FilterError> {
+        let start = self.offset;
+        let mut left = self.parse_and()?;
+
+        loop {
+            self.skip_blank();
+            if !self.eat_keyword("or") {
+                return Ok(left);
+            }
+
+            self.skip_blank();
+            let right = self.parse_and()?;
+            left = self.expression(Kind::Or(Box::new(left), Box::new(right)), start);
+        }
+    }
+
+    fn parse_and(&mut self) -> Result<Expression, FilterError> {
+        let start = self.offset;
+        let mut left = self.parse_comparison()?;
+
+        loop {
+            self.skip_blank();
+            if !self.eat_keyword("and") {
+                return Ok(left);
+            }
+
+            self.skip_blank();
+            let right = self.parse_comparison()?;
+            left = self.expression(Kind::And(Box::new(left), Box::new(right)), start);
+        }
+    }
+
+    fn parse_comparison(&mut self) -> Result<Expression, FilterError> {
+        let start = self.offset;
+        let left = self.parse_postfix()?;
+        self.skip_blank();
+
+        let operators = [
+            ("==", Comparison::Equal),
+            ("!=", Comparison::NotEqual),
+            ("<=", Comparison::LessOrEqual),
+            (">=", Comparison::GreaterOrEqual),
+            ("<", Comparison::Less),
+            (">", Comparison::Greater),
+        ];
+
+        let Some((_, comparison)) = operators
+            .into_iter()
+            .find(|(operator, _)| self.eat(operator))
+        else {
+            return Ok(left);
+        };
+
+        self.skip_blank();
+        let right = self.parse_postfix()?;
+        let kind = Kind::Compare(Box::new(left), comparison, Box::new(right));
+
+        Ok(self.expression(kind, start))
+    }
+
+    // A term followed by any number of `.name`, `[index]` and `[]`
+    fn parse_postfix(&mut self) -> Result<Expression, FilterError> {
+        let mut expression = self.parse_term()?;
+
+        loop {
+            let start = self.offset;
+
+            if self.rest().starts_with('.') {
+                self.offset += 1;
+                let name = self.parse_field_name()?;
+                expression = self.expression(Kind::Field(Box::new(expression), name), start);
+            } else if self.eat("[") {
+                expression = self.parse_brackets(expression, start)?;
+            } else {
+                return Ok(expression);
+            }
+        }
+    }
+
+    // The name after a `.`, either an identifier or a string
+    fn parse_field_name(&mut self) -> Result<String, FilterError> {
+        if self.rest().starts_with('"') {
+            return self.parse_string();
+        }
+
+        let len = Self::identifier_len(self.rest());
+        if len == 0 {
+            return self.unexpected("a field name");
+        }
+
+        let name = self.rest()[..len].to_owned();
+        self.offset += len;
+        Ok(name)
+    }
+
+    // `[]` or `[index]` after `target`, the `[` is already consumed
+    fn parse_brackets(
+        &mut self,
+        target: Expression,
+        start: usize,
+    ) -> Result<Expression, FilterError> {
+        self.skip_blank();
+
+        if self.eat("]") {
+            return Ok(self.expression(Kind::Iterate(Box::new(target)), start));
+        }
+
+        let index = self.parse_pipe()?;
+        self.skip_blank();
+        self.expect("]")?;
+
+        Ok(self.expression(Kind::Index(Box::new(target), Box::new(index)), start))
+    }
+
+    fn parse_term(&mut self) -> Result<Expression, FilterError> {
+        let start = self.offset;
+
+        match self.peek() {
+            Some('.') => {
+                self.offset += 1;
+                let identity = self.expression(Kind::Identity, start);
+
+                if self.rest().starts_with('"') || Self::identifier_len(self.rest()) > 0 {
+                    let name = self.parse_field_name()?;
+                    return Ok(self.expression(Kind::Field(Box::new(identity), name), start));
+                }
+
+                Ok(identity)
+            }
+            Some('"') => {
+                let string = self.parse_string()?;
+                Ok(self.expression(Kind::Literal(Json::String(string)), start))
+            }
+            Some('-' | '0'..='9') => {
+                let number = self.parse_number()?;
+                Ok(self.expression(Kind::Literal(Json::Number(number)), start))
+            }
+            Some('(') => {
+                self.offset += 1;
+                self.skip_blank();
+                let expression = self.parse_pipe()?;
+                self.skip_blank();
+                self.expect(")")?;
+                Ok(expression)
+            }
+            Some('[') => {
+                self.offset += 1;
+                self.skip_blank();
+
+                if self.eat("]") {
+                    return Ok(self.expression(Kind::Array(None), start));
+                }
+
+                let elements = self.parse_pipe()?;
+                self.skip_blank();
+                self.expect("]")?;
+                Ok(self.expression(Kind::Array(Some(Box::new(elements))), start))
+            }
+            Some('{') => self.parse_object(),
+            Some(c) if c.is_ascii_alphabetic() || c == '_' => self.parse_function(),
+            _ => self.unexpected("a filter"),
+        }
+    }
+
+    fn parse_function(&mut self) -> Result<Expression, FilterError> {
+        let start = self.offset;
+        let len = Self::identifier_len(self.rest());
+        let name = &self.rest()[..len];
+        self.offset += len;
+
+        let kind = match name {
+            "true" => Kind::Literal(Json::Boolean(true)),
+            "false" => Kind::Literal(Json::Boolean(false)),
+            "null" => Kind::Literal(Json::Null),
+            "keys" => Kind::Keys,
+            "length" => Kind::Length,
+            "not" => Kind::Not,
+            "empty" => Kind::Empty,
+            "select" | "map" => {
+                self.skip_blank();
+                self.expect("(")?;
+                self.skip_blank();
+                let argument = Box::new(self.parse_pipe()?);
+                self.skip_blank();
+                self.expect(")")?;
+
+                match name {
+                    "select" => Kind::Select(argument),
+                    _ => Kind::Map(argument),
+                }
+            }
+            _ => return self.error(start, format!("Unknown function `{}`", name)),
+        };
+
+        Ok(self.expression(kind, start))
+    }
+
+    // Keys are names, strings or expressions in parentheses. `{name}` is short for `{name: .name}`.
+    fn parse_object(&mut self) -> Result<Expression, FilterError> {
+        let start = self.offset;
+        self.offset += 1;
+
+        let mut entries = vec![];
+
+        loop {
+            self.skip_blank();
+            if entries.is_empty() && self.eat("}") {
+                break;
+            }
+
+            let key_start = self.offset;
+            let (key, name) = match self.peek() {
+                Some('(') => {
+                    self.offset += 1;
+                    self.skip_blank();
+                    let key = self.parse_pipe()?;
+                    self.skip_blank();
+                    self.expect(")")?;
+                    (key, None)
+                }
+                Some('"') => {
+                    let name = self.parse_string()?;
+                    let key = self.expression(Kind::Literal(Json::String(name.clone())), key_start);
+                    (key, Some(name))
+                }
+                _ => {
+                    let len = Self::identifier_len(self.rest());
+                    if len == 0 {
+                        return self.unexpected("an object key");
+                    }
+
+                    let name = self.rest()[..len].to_owned();
+                    self.offset += len;
+                    let key = self.expression(Kind::Literal(Json::String(name.clone())), key_start);
+                    (key, Some(name))
+                }
+            };
+
+            self.skip_blank();
+            let value = if self.eat(":") {
+                self.skip_blank();
+                self.parse_object_value()?
+            } else if let Some(name) = name {
+                let identity = self.expression(Kind::Identity, key_start);
+                self.expression(Kind::Field(Box::new(identity), name), key_start)
+            } else {
+                return self.unexpected("`:`");
+            };
+
+            entries.push((key, value));
+
+            self.skip_blank();
+            if self.eat("}") {
+                break;
+            }
+            self.expect(",")?;
+        }
+
+        Ok(self.expression(Kind::Object(entries), start))
+    }
+
+    // Like in jq, values can only contain commas and operators inside parentheses
+    fn parse_object_value(&mut self) -> Result<Expression, FilterError> {
+        let start = self.offset;
+        let left = self.parse_postfix()?;
+        self.skip_blank();
+
+        if !self.eat("|") {
+            return Ok(left);
+        }
+
+        self.skip_blank();
+        let right = self.parse_object_value()?;
+        Ok(self.expression(Kind::Pipe(Box::new(left), Box::new(right)), start))
+    }
+
+    // A double-quoted string with JSON escape sequences, which the JSON parser decodes
+    fn parse_string(&mut self) -> Result<String, FilterError> {
+        let start = self.offset;
+        let mut escaped = false;
+
+        let end = self.rest()[1..].find(|c| {
+            let end = c == '"' && !escaped;
+            escaped = c == '\\' && !escaped;
+            end
+        });
+
+        let Some(end) = end else {
+            self.offset = self.filter.len();
+            return self.error(start, "Unterminated string".to_owned());
+        };
+
+        let text = &self.rest()[..end + 2];
+        self.offset += text.len();
+
+        match parse(text, ParseOptions::default()) {
+            Ok(Json::String(string)) => Ok(string),
+            Ok(_) => unreachable!("a quoted text is parsed as a string"),
+            Err(error) => {
+                let span = error.span().shift(Span::new(self.filter, start, 0));
+                Err(FilterError::new(span, error.message().to_owned()))
+            }
+        }
+    }
+
+    fn parse_number(&mut self) -> Result<Number, FilterError> {
+        let start = self.offset;
+        let len = self
+            .rest()
+            .find(|c: char| !c.is_ascii_digit() && !matches!(c, '-' | '+' | '.' | 'e' | 'E'))
+            .unwrap_or(self.rest().len());
+
+        let text = &self.rest()[..len];
+        self.offset += len;
+
+        match text.parse() {
+            Ok(number) => Ok(number),
+            Err(_) => self.error(start, format!("`{}` is not a valid number", text)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::filter::{Filter, FilterError};
+    use crate::parser::{parse, Json, ParseOptions};
+    use crate::span::Span;
+
+    fn json(input: &str) -> Json {
+        parse(input, ParseOptions::default()).unwrap()
+    }
+
+    fn apply(filter: &str, input: &str) -> Result<Vec<Json>, FilterError> {
+        Filter::parse(filter)?.apply(&json(input))
+    }
+
+    const STORE: &str = r#"{
+        "name": "store",
+        "items": [
+            {"name": "pen", "price": 1.5, "tags": ["office"]},
+            {"name": "book", "price": 12, "tags": []},
+            {"name": "lamp", "price": 30, "stock": null}
+        ]
+    }"#;
+
+    macro_rules! filter_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (filter, input, expected): (&str, &str, &[&str]) = $value;
+                let expected: Vec<_> = expected.iter().map(|output| json(output)).collect();
+
+                assert_eq!(apply(filter, input).unwrap(), expected);
+            }
+        )*
+        }
+    }
+
+    filter_tests! {
+        test_filter_identity: (".", "[1, {\"a\": null}]", &["[1,{\"a\":null}]"]),
+        test_filter_field: (".name", STORE, &["\"store\""]),
+        test_filter_quoted_field: (".\"a b\"", "{\"a b\": 1}", &["1"]),
+        test_filter_missing_field: (".missing.deeper", STORE, &["null"]),
+        test_filter_nested: (".items[0].tags[0]", STORE, &["\"office\""]),
+        test_filter_negative_index: (".items[-1].name", STORE, &["\"lamp\""]),
+        test_filter_index_out_of_bounds: (".[5]", "[1]", &["null"]),
+        test_filter_iterate: (".items[].name", STORE, &["\"pen\"", "\"book\"", "\"lamp\""]),
+        test_filter_iterate_object: (".[]", "{\"a\": [1, 2]}", &["[1, 2]"]),
+        test_filter_pipe: (".items | .[1] | .price", STORE, &["12"]),
+        test_filter_pipe_built_value: ("{a: [.[0], 2]} | .a[], .a", "[1]", &["1", "2", "[1,2]"]),
+        test_filter_comma: (".a, .b", "{\"a\": 1, \"b\": 2}", &["1", "2"]),
+        test_filter_select: (
+                ".items[] | select(.price < 10 or .name == \"lamp\") | .name",
+                STORE,
+                &["\"pen\"", "\"lamp\""],
+            ),
+        test_filter_select_exists: (".items[] | select(.tags) | .name", STORE, &["\"pen\"", "\"book\""]),
+        test_filter_select_and_not: (".[] | select(. > 1 and (. == 3 | not))", "[1, 2, 3, 4]", &["2", "4"]),
+        test_filter_map: ("map(.price >= 12)", "[{\"price\": 1.5}, {\"price\": 12}]", &["[false,true]"]),
+        test_filter_map_object: ("map(length)", "{\"a\": \"xy\"}", &["[2]"]),
+        test_filter_object: (".items[0] | {name, cost: .price, (.tags[0]): true}", STORE, &["{\"name\":\"pen\",\"cost\":1.5,\"office\":true}"]),
+        test_filter_object_combinations: ("{a: (1, 2), \"b\": .}", "null", &["{\"a\":1,\"b\":null}", "{\"a\":2,\"b\":null}"]),
+        test_filter_array: ("[.items[].price]", STORE, &["[1.5,12,30]"]),
+        test_filter_keys: (".items[2] | keys", STORE, &["[\"name\",\"price\",\"stock\"]"]),
+        test_filter_keys_array: ("keys", "[5, 6]", &["[0,1]"]),
+        test_filter_length: (".[] | length", "[null, -2.5, \"h\u{e9}\", [1, 2], {\"a\": 1}]", &["0", "2.5", "2", "2", "1"]),
+        test_filter_literals: ("null, true, \"a\\n\", -1.5e2", "0", &["null", "true", "\"a\\n\"", "-1.5e2"]),
+        test_filter_empty: ("[.[] | select(. != 2), empty]", "[1, 2, 3]", &["[1,3]"]),
+        test_filter_compare_types: ("[null < false, false < 0, 0 < \"\", \"\" < [], [] < {}]", "0", &["[true,true,true,true,true]"]),
+        test_filter_compare_outputs: ("[(1, 2) == (1, 3)]", "null", &["[true,false,false,false]"]),
+        test_filter_index_outputs: ("[.[][0, 1]]", "[[1, 2], [3, 4]]", &["[1,3,2,4]"]),
+        test_filter_compare_numbers: ("[1.0 == 1, 0.1 < 0.10000000000000000001, [1, 2] < [1, 3]]", "0", &["[true,true,true]"]),
+    }
+
+    macro_rules! filter_error_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (filter, input, offset, len, message) = $value;
+                let expected = FilterError {
+                    span: Span::new(filter, offset, len),
+                    message: message.to_owned(),
+                };
+
+                assert_eq!(apply(filter, input), Err(expected));
+            }
+        )*
+        }
+    }
+
+    filter_error_tests! {
+        test_filter_error_unexpected: (".a |", "null", 4, 0, "Unexpected end of filter, expected a filter"),
+        test_filter_error_unclosed: (".[0", "null", 3, 0, "Unexpected end of filter, expected `]`"),
+        test_filter_error_trailing: (".a )", "null", 3, 1, "Unexpected `)`, expected `|` or the end of the filter"),
+        test_filter_error_unknown_function: ("to_entries", "null", 0, 10, "Unknown function `to_entries`"),
+        test_filter_error_unterminated_string: (".\"a", "null", 1, 2, "Unterminated string"),
+        test_filter_error_escape: ("\"\\x\"", "null", 1, 2, "Invalid escape sequence `\\x`"),
+        test_filter_error_field_of_number: (".a.b", "{\"a\": 1}", 2, 2, "Cannot look up `b` in number"),
+        test_filter_error_iterate: (".[] | .[]", "[true]", 7, 2, "Cannot iterate over boolean"),
+        test_filter_error_index_type: (".[\"a\"]", "[1]", 1, 5, "Cannot look up `a` in array"),
+        test_filter_error_object_key: ("{(1): 2}", "null", 2, 1, "Object keys must be strings, found number"),
+        test_filter_error_length: ("length", "true", 0, 6, "boolean has no length"),
+    }
+}
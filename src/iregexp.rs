@@ -0,0 +1,302 @@
+use std::iter::Peekable;
+use std::str::Chars;
+
+use regex::Regex;
+
+// The Unicode general categories I-Regexp allows in `\p{..}` and `\P{..}`
+const CATEGORIES: &[&str] = &[
+    "L", "Lu", "Ll", "Lt", "Lm", "Lo", "M", "Mn", "Mc", "Me", "N", "Nd", "Nl", "No", "P", "Pc",
+    "Pd", "Ps", "Pe", "Pi", "Pf", "Po", "Z", "Zs", "Zl", "Zp", "S", "Sm", "Sc", "Sk", "So", "C",
+    "Cc", "Cf", "Co", "Cn",
+];
+
+// Compiles an I-Regexp (RFC 9485) that matches a whole string, or any part of it if not `whole`.
+// Returns `None` for anything that is not an I-Regexp, even if the `regex` crate would accept it.
+pub(crate) fn compile(pattern: &str, whole: bool) -> Option<Regex> {
+    let mut translator = Translator {
+        chars: pattern.chars().peekable(),
+        output: String::new(),
+    };
+
+    translator.alternation()?;
+    if translator.chars.next().is_some() {
+        return None;
+    }
+
+    if whole {
+        Regex::new(&format!(r"\A(?:{})\z", translator.output)).ok()
+    } else {
+        Regex::new(&translator.output).ok()
+    }
+}
+
+// Rewrites I-Regexp syntax into `regex` syntax, every method returns `None` on invalid input
+struct Translator<'p> {
+    chars: Peekable<Chars<'p>>,
+    output: String,
+}
+
+impl<'p> Translator<'p> {
+    fn eat(&mut self, c: char) -> bool {
+        self.chars.next_if_eq(&c).is_some()
+    }
+
+    fn alternation(&mut self) -> Option<()> {
+        self.branch()?;
+
+        while self.eat('|') {
+            self.output.push('|');
+            self.branch()?;
+        }
+
+        Some(())
+    }
+
+    fn branch(&mut self) -> Option<()> {
+        while !matches!(self.chars.peek(), None | Some('|' | ')')) {
+            self.atom()?;
+            self.quantifier()?;
+        }
+
+        Some(())
+    }
+
+    fn atom(&mut self) -> Option<()> {
+        match self.chars.next()? {
+            '(' => {
+                self.output.push_str("(?:");
+                self.alternation()?;
+                self.eat(')').then_some(())?;
+                self.output.push(')');
+            }
+            // Unlike in `regex`, the dot doesn't match carriage returns either
+            '.' => self.output.push_str(r"[^\n\r]"),
+            '[' => self.class()?,
+            '\\' => {
+                let escape = self.escape()?;
+                self.output.push_str(&escape);
+            }
+            '*' | '+' | '?' | '{' | '}' | ']' => return None,
+            c => self.output.push_str(&literal(c)),
+        }
+
+        Some(())
+    }
+
+    fn quantifier(&mut self) -> Option<()> {
+        match self.chars.peek() {
+            Some(&c @ ('*' | '+' | '?')) => {
+                self.chars.next();
+                self.output.push(c);
+            }
+            Some('{') => {
+                self.chars.next();
+                let min = self.integer()?;
+
+                let max = match self.eat(',') {
+                    false => Some(min),
+                    true if self.chars.peek() == Some(&'}') => None,
+                    true => Some(self.integer()?),
+                };
+                self.eat('}').then_some(())?;
+
+                match max {
+                    Some(max) if max < min => return None,
+                    Some(max) if max == min => self.output.push_str(&format!("{{{}}}", min)),
+                    Some(max) => self.output.push_str(&format!("{{{},{}}}", min, max)),
+                    None => self.output.push_str(&format!("{{{},}}", min)),
+                }
+            }
+            _ => {}
+        }
+
+        Some(())
+    }
+
+    fn integer(&mut self) -> Option<u32> {
+        let mut digits = String::new();
+
+        while let Some(digit) = self.chars.next_if(char::is_ascii_digit) {
+            digits.push(digit);
+        }
+
+        digits.parse().ok()
+    }
+
+    // Everything after a backslash, translated into `regex` syntax
+    fn escape(&mut self) -> Option<String> {
+        match self.chars.next()? {
+            'n' => Some(r"\n".to_owned()),
+            'r' => Some(r"\r".to_owned()),
+            't' => Some(r"\t".to_owned()),
+            c @ ('(' | ')' | '*' | '+' | '-' | '.' | '?' | '[' | '\\' | ']' | '^' | '{' | '|'
+            | '}') => Some(format!(r"\{}", c)),
+            c @ ('p' | 'P') => {
+                self.eat('{').then_some(())?;
+
+                let mut name = String::new();
+                while let Some(c) = self.chars.next_if(|&c| c != '}') {
+                    name.push(c);
+                }
+                self.eat('}').then_some(())?;
+
+                CATEGORIES
+                    .contains(&name.as_str())
+                    .then(|| format!(r"\{}{{{}}}", c, name))
+            }
+            _ => None,
+        }
+    }
+
+    // A bracketed class like `[^a-z\p{Lu}-]`, the `[` is already consumed
+    fn class(&mut self) -> Option<()> {
+        self.output.push('[');
+        if self.eat('^') {
+            self.output.push('^');
+        }
+
+        // A `-` is a literal at the start and at the end of the class
+        let mut empty = true;
+        if self.eat('-') {
+            self.output.push_str(r"\-");
+            empty = false;
+        }
+
+        loop {
+            let next = *self.chars.peek()?;
+
+            match next {
+                ']' if !empty => break,
+                '-' => {
+                    self.chars.next();
+                    (self.chars.peek() == Some(&']')).then_some(())?;
+                    self.output.push_str(r"\-");
+                }
+                '\\' if matches!(self.chars.clone().nth(1), Some('p' | 'P')) => {
+                    self.chars.next();
+                    let escape = self.escape()?;
+                    self.output.push_str(&escape);
+                }
+                _ => {
+                    let first = self.class_char()?;
+                    self.output.push_str(&literal(first));
+
+                    // A `-` followed by `]` is a literal, which the next iteration handles
+                    let mut lookahead = self.chars.clone();
+                    if lookahead.next() == Some('-')
+                        && !matches!(lookahead.next(), None | Some(']'))
+                    {
+                        self.chars.next();
+                        let last = self.class_char()?;
+                        (first <= last).then_some(())?;
+
+                        self.output.push('-');
+                        self.output.push_str(&literal(last));
+                    }
+                }
+            }
+
+            empty = false;
+        }
+
+        self.chars.next();
+        self.output.push(']');
+        Some(())
+    }
+
+    // A single character in a class, which may be escaped
+    fn class_char(&mut self) -> Option<char> {
+        match self.chars.next()? {
+            '\\' => match self.chars.next()? {
+                'n' => Some('\n'),
+                'r' => Some('\r'),
+                't' => Some('\t'),
+                c @ ('(' | ')' | '*' | '+' | '-' | '.' | '?' | '[' | '\\' | ']' | '^' | '{'
+                | '|' | '}') => Some(c),
+                _ => None,
+            },
+            '[' | ']' | '-' => None,
+            c => Some(c),
+        }
+    }
+}
+
+// Also escapes the characters that have a meaning inside `regex` classes, like `&&` and `~~`
+fn literal(c: char) -> String {
+    regex::escape(c.encode_utf8(&mut [0; 4]))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::iregexp::compile;
+
+    macro_rules! match_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (pattern, matching, other): (&str, &[&str], &[&str]) = $value;
+                let regex = compile(pattern, true).unwrap();
+
+                for text in matching {
+                    assert!(regex.is_match(text), "`{}` should match `{}`", pattern, text);
+                }
+                for text in other {
+                    assert!(!regex.is_match(text), "`{}` should not match `{}`", pattern, text);
+                }
+            }
+        )*
+        }
+    }
+
+    match_tests! {
+        test_iregexp_literal: ("abc", &["abc"], &["abcd", "xabc", "ab"]),
+        test_iregexp_dot: ("a.c", &["abc", "a\u{e9}c"], &["a\nc", "a\rc", "ac"]),
+        test_iregexp_anchors_are_literals: ("^a$", &["^a$"], &["a"]),
+        test_iregexp_alternation: ("ab|c(d|e)", &["ab", "cd", "ce"], &["c", "abcd"]),
+        test_iregexp_quantifiers: ("a*b+c?d{2}e{1,}f{0,1}", &["bdde", "aabbcddeef"], &["bde", "bddeff"]),
+        test_iregexp_class: ("[a-c_\\]\\-]+", &["ab_]-c"], &["d", "[", "\\"]),
+        test_iregexp_negated_class: ("[^a-c]", &["d", "\n"], &["a"]),
+        test_iregexp_dash_at_edges: ("[-a][a-]", &["-a", "aa", "a-"], &["b-"]),
+        test_iregexp_class_operators: ("[a&&b]", &["&", "a"], &["ab"]),
+        test_iregexp_categories: ("\\p{Lu}\\P{L}[\\p{Nd}x]", &["A11", "\u{c9}-x"], &["a11", "AAx"]),
+        test_iregexp_unassigned: ("\\p{Cn}", &["\u{378}"], &["a"]),
+        test_iregexp_escapes: ("\\.\\n\\t\\{\\}", &[".\n\t{}"], &["a\n\t{}"]),
+    }
+
+    macro_rules! invalid_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                assert!(compile($value, false).is_none(), "`{}` should be rejected", $value);
+            }
+        )*
+        }
+    }
+
+    invalid_tests! {
+        test_iregexp_invalid_perl_class: "\\d",
+        test_iregexp_invalid_word_boundary: "a\\b",
+        test_iregexp_invalid_dollar_escape: "a\\$",
+        test_iregexp_invalid_group_flags: "(?i)a",
+        test_iregexp_invalid_lazy: "a*?",
+        test_iregexp_invalid_double_quantifier: "a**",
+        test_iregexp_invalid_unclosed_group: "(a",
+        test_iregexp_invalid_unopened_group: "a)",
+        test_iregexp_invalid_empty_class: "[]",
+        test_iregexp_invalid_reversed_range: "[z-a]",
+        test_iregexp_invalid_reversed_count: "a{3,2}",
+        test_iregexp_invalid_category: "\\p{IsBasicLatin}",
+        test_iregexp_invalid_surrogate_category: "\\p{Cs}",
+        test_iregexp_invalid_leading_quantifier: "*a",
+    }
+
+    #[test]
+    fn test_iregexp_search() {
+        let regex = compile("b.", false).unwrap();
+
+        assert!(regex.is_match("abc"));
+        assert!(!regex.is_match("ab"));
+    }
+}
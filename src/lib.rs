@@ -0,0 +1,186 @@
+use std::io::Read;
+use std::path::Path;
+
+pub mod borrowed;
+pub mod diagnostic;
+pub mod error;
+pub mod events;
+pub mod filter;
+mod iregexp;
+pub mod map;
+pub mod ndjson;
+pub mod number;
+pub mod parser;
+pub mod patch;
+pub mod path;
+pub mod pointer;
+pub mod pretty;
+pub mod push;
+pub mod reader;
+pub mod sequence;
+pub mod serializer;
+pub mod span;
+pub mod tokenizer;
+
+pub use crate::borrowed::BorrowedJson;
+pub use crate::error::Error;
+pub use crate::events::{Event, EventReader};
+pub use crate::filter::{Filter, FilterError};
+pub use crate::map::Map;
+pub use crate::ndjson::{BadLines, NdjsonOptions, NdjsonReader, NdjsonWriter};
+pub use crate::number::{Decimal, Number};
+pub use crate::parser::{DuplicateKeys, Json, ParseOptions};
+pub use crate::patch::{Operation, Patch, PatchError};
+pub use crate::path::{JsonPath, Node, PathError};
+pub use crate::pointer::PointerError;
+pub use crate::pretty::{to_string_pretty, to_writer_pretty, PrettyOptions};
+pub use crate::push::PushParser;
+pub use crate::sequence::{parse_concatenated, parse_json_seq};
+pub use crate::serializer::{to_string, to_writer};
+pub use crate::tokenizer::{TokenizeOptions, Tokenizer};
+
+#[cfg(feature = "arbitrary_precision")]
+pub use bigdecimal::BigDecimal;
+#[cfg(feature = "arbitrary_precision")]
+pub use num_bigint::BigInt;
+
+pub fn parse(input: &str) -> Result<Json, Error> {
+    parse_with_options(input, ParseOptions::default())
+}
+
+pub fn parse_with_options(input: &str, options: ParseOptions) -> Result<Json, Error> {
+    parser::parse(input, options)
+}
+
+// Parses without copying strings that contain no escape sequences
+pub fn parse_borrowed(input: &str) -> Result<BorrowedJson<'_>, Error> {
+    parse_borrowed_with_options(input, ParseOptions::default())
+}
+
+pub fn parse_borrowed_with_options(
+    input: &str,
+    options: ParseOptions,
+) -> Result<BorrowedJson<'_>, Error> {
+    borrowed::parse(input, options)
+}
+
+pub fn from_reader<R: Read>(reader: R) -> Result<Json, Error> {
+    from_reader_with_options(reader, ParseOptions::default())
+}
+
+pub fn from_reader_with_options<R: Read>(reader: R, options: ParseOptions) -> Result<Json, Error> {
+    reader::parse(reader, options)
+}
+
+pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Json, Error> {
+    from_file_with_options(path, ParseOptions::default())
+}
+
+pub fn from_file_with_options<P: AsRef<Path>>(
+    path: P,
+    options: ParseOptions,
+) -> Result<Json, Error> {
+    reader::parse_file(path, options)
+}
+
+// Alias of `parse`, for those used to `std::str::FromStr`
+pub fn from_str(input: &str) -> Result<Json, Error> {
+    parse(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::ParseError;
+    use crate::span::Span;
+    use crate::tokenizer::TokenizeError;
+    use crate::{
+        from_str, parse, parse_borrowed, parse_with_options, to_string, Error, Json, ParseOptions,
+    };
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            parse("[true, null]"),
+            Ok(Json::Array(vec![Json::Boolean(true), Json::Null]))
+        );
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!(from_str("\"a\""), Ok(Json::String("a".to_owned())));
+    }
+
+    #[test]
+    fn test_parse_borrowed() {
+        let input = "{\"id\": 18446744073709551615, \"tags\": [\"a\", \"b\"]}";
+        let json = parse_borrowed(input).unwrap();
+
+        assert_eq!(json.into_owned(), parse(input).unwrap());
+    }
+
+    #[test]
+    fn test_parse_tokenize_error() {
+        assert_eq!(
+            parse("[nul]"),
+            Err(Error::Tokenize(TokenizeError {
+                span: Span {
+                    offset: 1,
+                    len: 1,
+                    line: 1,
+                    column: 2
+                },
+                message: "Expected literal `null`".to_owned(),
+            }))
+        );
+    }
+
+    #[test]
+    fn test_parse_parse_error() {
+        let error = parse("[1 2]").unwrap_err();
+
+        assert_eq!(
+            error,
+            Error::Parse(ParseError {
+                span: Span {
+                    offset: 3,
+                    len: 1,
+                    line: 1,
+                    column: 4
+                },
+                message: "Unexpected token `2` in array".to_owned(),
+            })
+        );
+        assert_eq!(error.to_string(), "1:4: Unexpected token `2` in array");
+    }
+
+    #[test]
+    fn test_parse_with_options() {
+        let options = ParseOptions {
+            strict: false,
+            ..ParseOptions::default()
+        };
+        assert_eq!(
+            parse_with_options("\"\t\"", options),
+            Ok(Json::String("\t".to_owned()))
+        );
+    }
+
+    #[cfg(feature = "preserve_order")]
+    #[test]
+    fn test_round_trip_keeps_key_order() {
+        let input = "{\"z\":1,\"a\":{\"y\":2,\"b\":3},\"m\":4}";
+        assert_eq!(to_string(&parse(input).unwrap()), input);
+    }
+
+    #[test]
+    fn test_round_trip_keeps_numbers() {
+        let input = "[18446744073709551615,-0.0,1.10,1E+2,3.141592653589793238462643383279]";
+        assert_eq!(to_string(&parse(input).unwrap()), input);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let input = "[1,\"two\\n\",[null,false],{},-2.5e-8,1e100]";
+        assert_eq!(to_string(&parse(input).unwrap()), input);
+    }
+}
@@ -1,42 +1,140 @@
-use crate::parser::parse;
-use crate::tokenizer::tokenize;
-
-mod parser;
-mod tokenizer;
-
 use std::env;
+use std::fs;
+use std::io::{self, Read};
 use std::process::ExitCode;
 
+use rust_playground::diagnostic::Diagnostic;
+use rust_playground::{
+    from_reader_with_options, parse_with_options, to_string_pretty, DuplicateKeys, Filter,
+    FilterError, ParseOptions, PrettyOptions,
+};
+
 fn main() -> ExitCode {
     let args: Vec<String> = env::args().collect();
 
-    if args.len() != 2 {
-        println!("Usage: {} <json string>", args[0]);
-        std::process::exit(1);
+    let mut parse_options = ParseOptions::default();
+    let mut pretty_options = PrettyOptions::default();
+    let mut compact = false;
+    let mut text = None;
+    let mut inputs = vec![];
+
+    for arg in args.iter().skip(1) {
+        match arg.as_str() {
+            "--lenient" => parse_options.strict = false,
+            "--duplicate-keys=error" => parse_options.duplicate_keys = DuplicateKeys::Error,
+            "--duplicate-keys=first" => parse_options.duplicate_keys = DuplicateKeys::FirstWins,
+            "--duplicate-keys=last" => parse_options.duplicate_keys = DuplicateKeys::LastWins,
+            "--duplicate-keys=collect" => parse_options.duplicate_keys = DuplicateKeys::Collect,
+            "--compact" => compact = true,
+            "--sort-keys" => pretty_options.sort_keys = true,
+            _ => match arg.strip_prefix("--json=") {
+                Some(json) => text = Some(json),
+                None if arg.starts_with("--") => return usage(&args[0]),
+                None => inputs.push(arg.as_str()),
+            },
+        }
     }
 
-    let tokenized = tokenize(&args[1]);
+    // Earlier versions took the JSON as the only argument, which still works if it is valid JSON
+    if let ([argument], None) = (inputs.as_slice(), text) {
+        if parse_with_options(argument, parse_options.clone()).is_ok() {
+            text = Some(argument);
+            inputs.clear();
+        }
+    }
 
-    match tokenized {
-        Ok(tokens) => {
-            let parsed = parse(tokens);
-            match parsed {
-                Ok(json) => {
-                    println!("{:?}", json);
-                    ExitCode::from(0)
-                }
-                Err(error) => {
-                    println!("Parse Error at token {}: {}", error.offset, error.message);
-                    ExitCode::from(1)
-                }
+    // Without a filter the input is printed as is, like jq's `.`
+    let (filter, file) = match (inputs.as_slice(), text) {
+        ([], _) => (".", None),
+        ([filter], _) => (*filter, None),
+        ([filter, file], None) => (*filter, Some(*file)),
+        _ => {
+            return usage(&args[0]);
+        }
+    };
+
+    let parsed_filter = match Filter::parse(filter) {
+        Ok(parsed_filter) => parsed_filter,
+        Err(error) => {
+            eprintln!("{}", filter_diagnostic(filter, &error));
+
+            // The only argument may have been meant as JSON, like in earlier versions
+            if file.is_none() && text.is_none() {
+                eprintln!("note: to pass JSON as an argument, use --json=<json>");
+                return usage(&args[0]);
             }
+            return ExitCode::from(1);
+        }
+    };
+
+    // The whole input is kept, so errors can show the line they are on
+    let (source, read) = match (text, file) {
+        (Some(text), _) => (None, Ok(text.as_bytes().to_vec())),
+        (None, Some(file)) if file != "-" => (Some(file), fs::read(file)),
+        (None, _) => {
+            let mut bytes = vec![];
+            let read = io::stdin().lock().read_to_end(&mut bytes);
+            (Some("<stdin>"), read.map(|_| bytes))
         }
+    };
+
+    let bytes = match read {
+        Ok(bytes) => bytes,
         Err(error) => {
-            println!(
-                "Tokenize Error at offset {}: {}",
-                error.offset, error.message
+            eprintln!(
+                "error: Cannot read {}: {}",
+                source.unwrap_or("input"),
+                error
             );
+            return ExitCode::from(1);
+        }
+    };
+
+    // Invalid UTF-8 is reported by the parser, the lossy text is only used to show the error
+    let json = match from_reader_with_options(bytes.as_slice(), parse_options) {
+        Ok(json) => json,
+        Err(error) => {
+            let input = String::from_utf8_lossy(&bytes);
+            let diagnostic = Diagnostic {
+                source,
+                ..Diagnostic::from_error(&input, &error)
+            };
+
+            eprintln!("{}", diagnostic);
+            return ExitCode::from(1);
+        }
+    };
+
+    match parsed_filter.apply(&json) {
+        Ok(outputs) => {
+            for output in outputs {
+                if compact {
+                    println!("{}", output);
+                } else {
+                    println!("{}", to_string_pretty(&output, pretty_options.clone()));
+                }
+            }
+            ExitCode::from(0)
+        }
+        Err(error) => {
+            eprintln!("{}", filter_diagnostic(filter, &error));
             ExitCode::from(1)
         }
     }
 }
+
+fn usage(program: &str) -> ExitCode {
+    eprintln!(
+        "Usage: {} [--lenient] [--duplicate-keys=error|first|last|collect] [--compact] [--sort-keys] [--json=<json>] [filter] [file]",
+        program
+    );
+    ExitCode::from(1)
+}
+
+// The help in diagnostics is about JSON input, so it is left out for filters
+fn filter_diagnostic<'a>(filter: &'a str, error: &FilterError) -> Diagnostic<'a> {
+    Diagnostic {
+        help: None,
+        ..Diagnostic::new(filter, error.span, &error.message)
+    }
+}
@@ -0,0 +1,325 @@
+#[cfg(not(feature = "preserve_order"))]
+use std::collections::hash_map;
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::parser::Json;
+
+// Keys of a JSON object with their values.
+// With the `preserve_order` feature (on by default) iteration follows insertion order,
+// without it the map is a plain `HashMap` and iteration order is unspecified.
+#[derive(Default, Clone)]
+pub struct Map {
+    #[cfg(feature = "preserve_order")]
+    entries: Vec<(String, Json)>,
+    // Position of each key in `entries`
+    #[cfg(feature = "preserve_order")]
+    indices: HashMap<String, usize>,
+
+    #[cfg(not(feature = "preserve_order"))]
+    entries: HashMap<String, Json>,
+}
+
+#[cfg(feature = "preserve_order")]
+impl Map {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: Vec::with_capacity(capacity),
+            indices: HashMap::with_capacity(capacity),
+        }
+    }
+
+    // Replacing the value of an existing key keeps its position
+    pub fn insert(&mut self, key: String, value: Json) -> Option<Json> {
+        match self.indices.get(&key) {
+            Some(&index) => Some(std::mem::replace(&mut self.entries[index].1, value)),
+            None => {
+                self.indices.insert(key.clone(), self.entries.len());
+                self.entries.push((key, value));
+                None
+            }
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        self.indices.get(key).map(|&index| &self.entries[index].1)
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Json> {
+        let index = *self.indices.get(key)?;
+        Some(&mut self.entries[index].1)
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.indices.contains_key(key)
+    }
+
+    // Keeps the order of the remaining keys, which takes linear time
+    pub fn remove(&mut self, key: &str) -> Option<Json> {
+        let index = self.indices.remove(key)?;
+        let (_, value) = self.entries.remove(index);
+
+        for other in self.indices.values_mut() {
+            if *other > index {
+                *other -= 1;
+            }
+        }
+
+        Some(value)
+    }
+}
+
+#[cfg(not(feature = "preserve_order"))]
+impl Map {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: HashMap::with_capacity(capacity),
+        }
+    }
+
+    pub fn insert(&mut self, key: String, value: Json) -> Option<Json> {
+        self.entries.insert(key, value)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        self.entries.get(key)
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Json> {
+        self.entries.get_mut(key)
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.entries.contains_key(key)
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<Json> {
+        self.entries.remove(key)
+    }
+}
+
+impl Map {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            inner: self.entries.iter(),
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_> {
+        IterMut {
+            inner: self.entries.iter_mut(),
+        }
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &String> {
+        self.iter().map(|(key, _)| key)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &Json> {
+        self.iter().map(|(_, value)| value)
+    }
+}
+
+pub struct Iter<'a> {
+    #[cfg(feature = "preserve_order")]
+    inner: std::slice::Iter<'a, (String, Json)>,
+    #[cfg(not(feature = "preserve_order"))]
+    inner: hash_map::Iter<'a, String, Json>,
+}
+
+#[cfg(feature = "preserve_order")]
+impl<'a> Iterator for Iter<'a> {
+    type Item = (&'a String, &'a Json);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(key, value)| (key, value))
+    }
+}
+
+#[cfg(not(feature = "preserve_order"))]
+impl<'a> Iterator for Iter<'a> {
+    type Item = (&'a String, &'a Json);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+}
+
+pub struct IterMut<'a> {
+    #[cfg(feature = "preserve_order")]
+    inner: std::slice::IterMut<'a, (String, Json)>,
+    #[cfg(not(feature = "preserve_order"))]
+    inner: hash_map::IterMut<'a, String, Json>,
+}
+
+#[cfg(feature = "preserve_order")]
+impl<'a> Iterator for IterMut<'a> {
+    type Item = (&'a String, &'a mut Json);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(key, value)| (&*key, value))
+    }
+}
+
+#[cfg(not(feature = "preserve_order"))]
+impl<'a> Iterator for IterMut<'a> {
+    type Item = (&'a String, &'a mut Json);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+}
+
+pub struct IntoIter {
+    #[cfg(feature = "preserve_order")]
+    inner: std::vec::IntoIter<(String, Json)>,
+    #[cfg(not(feature = "preserve_order"))]
+    inner: hash_map::IntoIter<String, Json>,
+}
+
+impl Iterator for IntoIter {
+    type Item = (String, Json);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+}
+
+impl IntoIterator for Map {
+    type Item = (String, Json);
+    type IntoIter = IntoIter;
+
+    fn into_iter(self) -> IntoIter {
+        IntoIter {
+            inner: self.entries.into_iter(),
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a Map {
+    type Item = (&'a String, &'a Json);
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Iter<'a> {
+        self.iter()
+    }
+}
+
+impl FromIterator<(String, Json)> for Map {
+    fn from_iter<I: IntoIterator<Item = (String, Json)>>(iter: I) -> Self {
+        let mut map = Map::new();
+        for (key, value) in iter {
+            map.insert(key, value);
+        }
+        map
+    }
+}
+
+impl<const N: usize> From<[(String, Json); N]> for Map {
+    fn from(entries: [(String, Json); N]) -> Self {
+        entries.into_iter().collect()
+    }
+}
+
+// Like JSON objects, maps with the same keys and values are equal regardless of order
+impl PartialEq for Map {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self
+                .iter()
+                .all(|(key, value)| other.get(key) == Some(value))
+    }
+}
+
+impl fmt::Debug for Map {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::map::Map;
+    use crate::parser::Json;
+
+    fn map(keys: &[&str]) -> Map {
+        keys.iter()
+            .enumerate()
+            .map(|(index, key)| (key.to_string(), Json::Number(index.into())))
+            .collect()
+    }
+
+    #[test]
+    fn test_map_get() {
+        let mut map = map(&["a", "b"]);
+
+        assert_eq!(map.get("b"), Some(&Json::Number(1.into())));
+        assert_eq!(map.get("c"), None);
+        assert!(map.contains_key("a"));
+
+        *map.get_mut("a").unwrap() = Json::Null;
+        assert_eq!(map.get("a"), Some(&Json::Null));
+    }
+
+    #[test]
+    fn test_map_insert_existing() {
+        let mut map = map(&["a", "b"]);
+
+        assert_eq!(
+            map.insert("a".to_owned(), Json::Null),
+            Some(Json::Number(0.into()))
+        );
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get("a"), Some(&Json::Null));
+    }
+
+    #[test]
+    fn test_map_remove() {
+        let mut map = map(&["a", "b", "c"]);
+
+        assert_eq!(map.remove("a"), Some(Json::Number(0.into())));
+        assert_eq!(map.remove("a"), None);
+        assert_eq!(map.get("c"), Some(&Json::Number(2.into())));
+        assert_eq!(map.len(), 2);
+    }
+
+    #[test]
+    fn test_map_eq_ignores_order() {
+        let a = Map::from([
+            ("x".to_owned(), Json::Null),
+            ("y".to_owned(), Json::Boolean(true)),
+        ]);
+        let b = Map::from([
+            ("y".to_owned(), Json::Boolean(true)),
+            ("x".to_owned(), Json::Null),
+        ]);
+
+        assert_eq!(a, b);
+        assert_ne!(a, Map::new());
+    }
+
+    #[cfg(feature = "preserve_order")]
+    #[test]
+    fn test_map_preserves_order() {
+        let mut map = map(&["z", "a", "m", "b"]);
+        map.remove("a");
+        map.insert("z".to_owned(), Json::Null);
+        map.insert("c".to_owned(), Json::Null);
+
+        let keys: Vec<&String> = map.keys().collect();
+        assert_eq!(keys, ["z", "m", "b", "c"]);
+        assert_eq!(map.get("b"), Some(&Json::Number(3.into())));
+    }
+}
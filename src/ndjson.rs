@@ -0,0 +1,291 @@
+use std::io::{self, BufRead, Write};
+
+use crate::error::Error;
+use crate::parser::{parse, Json, ParseOptions};
+use crate::reader::{ReadError, BOM};
+use crate::span::Span;
+use crate::tokenizer::TokenizeError;
+
+// What to do with a line that does not contain exactly one valid JSON value
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum BadLines {
+    // Return the error, then stop reading
+    Abort,
+    // Leave the line out, its error is kept in `NdjsonReader::skipped`
+    Skip,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct NdjsonOptions {
+    pub parse: ParseOptions,
+    pub bad_lines: BadLines,
+}
+
+impl Default for NdjsonOptions {
+    fn default() -> Self {
+        Self {
+            parse: ParseOptions::default(),
+            bad_lines: BadLines::Abort,
+        }
+    }
+}
+
+// Reads newline delimited JSON (also known as JSON Lines), one value per line.
+// Blank lines are ignored, and error spans point into the whole input, so their line is the line number.
+pub struct NdjsonReader<R> {
+    reader: R,
+    options: NdjsonOptions,
+    // Bytes of the current line, reused between lines
+    line: Vec<u8>,
+    // Position of the start of the next line
+    position: Span,
+    skipped: Vec<Error>,
+    // The end of input or an error that stops reading was reached
+    done: bool,
+}
+
+impl<R: BufRead> NdjsonReader<R> {
+    pub fn new(reader: R, options: NdjsonOptions) -> Self {
+        Self {
+            reader,
+            options,
+            line: vec![],
+            position: Span::new("", 0, 0),
+            skipped: vec![],
+            done: false,
+        }
+    }
+
+    // Errors of the lines that were left out with `BadLines::Skip`.
+    // They are kept until `take_skipped` is called, so a long stream of bad lines keeps growing this list.
+    pub fn skipped(&self) -> &[Error] {
+        &self.skipped
+    }
+
+    // Returns the errors of the lines that were left out so far, and forgets them
+    pub fn take_skipped(&mut self) -> Vec<Error> {
+        std::mem::take(&mut self.skipped)
+    }
+
+    // Parses the next line that is not blank, returns `None` at the end of input
+    fn read_line(&mut self) -> Option<Result<Json, Error>> {
+        loop {
+            let mut start = self.position;
+
+            self.line.clear();
+            match self.reader.read_until(b'\n', &mut self.line) {
+                Ok(0) => return None,
+                Ok(_) => self.position = start.advance(&self.line),
+                Err(error) => {
+                    let span = start.advance(&self.line);
+                    return Some(Err(Error::Read(ReadError::new(span, error))));
+                }
+            }
+
+            let mut bytes = self.line.as_slice();
+            if start.offset == 0 {
+                if let Some(rest) = bytes.strip_prefix(BOM) {
+                    bytes = rest;
+                    start.offset = BOM.len();
+                }
+            }
+
+            let bytes = bytes.strip_suffix(b"\n").unwrap_or(bytes);
+            let bytes = bytes.strip_suffix(b"\r").unwrap_or(bytes);
+
+            if bytes.iter().all(u8::is_ascii_whitespace) {
+                continue;
+            }
+
+            let parsed = match std::str::from_utf8(bytes) {
+                Ok(text) => parse(text, self.options.parse.clone()),
+                Err(error) => Err(Error::Tokenize(TokenizeError::invalid_utf8(bytes, error))),
+            };
+
+            return Some(parsed.map_err(|error| error.shift(start)));
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for NdjsonReader<R> {
+    type Item = Result<Json, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            match self.read_line() {
+                None => self.done = true,
+                Some(Ok(json)) => return Some(Ok(json)),
+                Some(Err(error)) => {
+                    // Reading can't continue after an I/O error, whatever the options say
+                    if self.options.bad_lines == BadLines::Skip && !matches!(error, Error::Read(_))
+                    {
+                        self.skipped.push(error);
+                        continue;
+                    }
+
+                    self.done = true;
+                    return Some(Err(error));
+                }
+            }
+        }
+
+        None
+    }
+}
+
+// Writes values as compact JSON, one per line
+pub struct NdjsonWriter<W> {
+    writer: W,
+}
+
+impl<W: Write> NdjsonWriter<W> {
+    // Wrap `writer` in a `std::io::BufWriter` when it does unbuffered writes
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    pub fn write(&mut self, json: &Json) -> io::Result<()> {
+        // Compact JSON escapes newlines in strings, so every value stays on its own line
+        writeln!(self.writer, "{}", json)
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::Error;
+    use crate::ndjson::{BadLines, NdjsonOptions, NdjsonReader, NdjsonWriter};
+    use crate::parser::{Json, ParseError};
+    use crate::span::Span;
+    use crate::tokenizer::TokenizeError;
+
+    fn read(input: &[u8], bad_lines: BadLines) -> Vec<Result<Json, Error>> {
+        let options = NdjsonOptions {
+            bad_lines,
+            ..NdjsonOptions::default()
+        };
+
+        NdjsonReader::new(input, options).collect()
+    }
+
+    #[test]
+    fn test_ndjson_read() {
+        assert_eq!(
+            read(b"1\r\n\n  \n\"two\"\n[3]", BadLines::Abort),
+            vec![
+                Ok(Json::Number(1.into())),
+                Ok(Json::String("two".to_owned())),
+                Ok(Json::Array(vec![Json::Number(3.into())])),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ndjson_abort() {
+        assert_eq!(
+            read(b"1\n\n[2,\n3", BadLines::Abort),
+            vec![
+                Ok(Json::Number(1.into())),
+                Err(Error::Parse(ParseError {
+                    span: Span {
+                        offset: 6,
+                        len: 0,
+                        line: 3,
+                        column: 4
+                    },
+                    message: "Unexpected end of input".to_owned(),
+                })),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ndjson_skip() {
+        let input: &[u8] = b"\xef\xbb\xbf1 2\n{}\n\"\xff\"\n3\n";
+        let mut reader = NdjsonReader::new(
+            input,
+            NdjsonOptions {
+                bad_lines: BadLines::Skip,
+                ..NdjsonOptions::default()
+            },
+        );
+
+        assert_eq!(
+            reader.by_ref().collect::<Vec<_>>(),
+            vec![
+                Ok(Json::Object(Default::default())),
+                Ok(Json::Number(3.into()))
+            ]
+        );
+        assert_eq!(
+            reader.skipped(),
+            [
+                Error::Parse(ParseError {
+                    span: Span {
+                        offset: 5,
+                        len: 1,
+                        line: 1,
+                        column: 3
+                    },
+                    message: "Unexpected extra input found".to_owned(),
+                }),
+                Error::Tokenize(TokenizeError {
+                    span: Span {
+                        offset: 11,
+                        len: 1,
+                        line: 3,
+                        column: 2
+                    },
+                    message: "Invalid UTF-8".to_owned(),
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ndjson_take_skipped() {
+        let input: &[u8] = b"x\n1\ny\nz\n";
+        let mut reader = NdjsonReader::new(
+            input,
+            NdjsonOptions {
+                bad_lines: BadLines::Skip,
+                ..NdjsonOptions::default()
+            },
+        );
+
+        assert_eq!(reader.next(), Some(Ok(Json::Number(1.into()))));
+        assert_eq!(reader.take_skipped().len(), 1);
+        assert!(reader.skipped().is_empty());
+
+        assert_eq!(reader.next(), None);
+        let lines: Vec<_> = reader
+            .take_skipped()
+            .iter()
+            .map(|error| error.span().line)
+            .collect();
+        assert_eq!(lines, [3, 4]);
+    }
+
+    #[test]
+    fn test_ndjson_write() {
+        let mut writer = NdjsonWriter::new(vec![]);
+
+        writer
+            .write(&Json::String("multi\nline".to_owned()))
+            .unwrap();
+        writer
+            .write(&Json::Array(vec![Json::Null, Json::Boolean(true)]))
+            .unwrap();
+
+        let output = writer.into_inner();
+        assert_eq!(output, b"\"multi\\nline\"\n[null,true]\n");
+        assert_eq!(read(&output, BadLines::Abort).len(), 2);
+    }
+}
@@ -0,0 +1,856 @@
+use std::cmp::Ordering;
+use std::fmt;
+#[cfg(feature = "arbitrary_precision")]
+use std::ops::{Add, Mul, Sub};
+use std::str::FromStr;
+
+#[cfg(feature = "arbitrary_precision")]
+use bigdecimal::{BigDecimal, RoundingMode};
+#[cfg(feature = "arbitrary_precision")]
+use num_bigint::{BigInt, Sign};
+
+use crate::error::Error;
+use crate::parser::ParseError;
+use crate::span::Span;
+use crate::tokenizer::{TokenType, TokenizeOptions, Tokenizer};
+
+// A JSON number, stored as the text it was parsed from so no precision is lost
+#[derive(Clone)]
+pub struct Number {
+    lexeme: String,
+}
+
+impl Number {
+    // `lexeme` must be a valid JSON number, like the value of a `TokenType::Number` token
+    pub(crate) fn from_lexeme(lexeme: &str) -> Self {
+        Self {
+            lexeme: lexeme.to_owned(),
+        }
+    }
+
+    // Uses the shortest text that parses back to `value`, returns `None` for NaN and infinity
+    pub fn from_f64(value: f64) -> Option<Self> {
+        if !value.is_finite() {
+            return None;
+        }
+
+        // Same choice between decimal and exponent notation as JavaScript's `JSON.stringify`
+        let magnitude = value.abs();
+
+        let lexeme = if magnitude == 0.0 || (1e-6..1e21).contains(&magnitude) {
+            format!("{}", value)
+        } else {
+            format!("{:e}", value)
+        };
+
+        Some(Self { lexeme })
+    }
+
+    // The number exactly as it appeared in the input
+    pub fn as_str(&self) -> &str {
+        &self.lexeme
+    }
+
+    // Returns the closest float, or `None` if the number is too large for one
+    pub fn as_f64(&self) -> Option<f64> {
+        self.lexeme
+            .parse::<f64>()
+            .ok()
+            .filter(|float| float.is_finite())
+    }
+
+    // The integer accessors also accept numbers like `1.0` or `1e3`, as long as the value is whole
+    pub fn as_i64(&self) -> Option<i64> {
+        self.as_i128()?.try_into().ok()
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        self.as_i128()?.try_into().ok()
+    }
+
+    pub fn as_i128(&self) -> Option<i128> {
+        match self.lexeme.parse::<i128>() {
+            Ok(integer) => Some(integer),
+            Err(_) => self.as_decimal().to_i128(),
+        }
+    }
+
+    pub fn as_decimal(&self) -> Decimal {
+        Decimal::from_lexeme(&self.lexeme)
+    }
+}
+
+#[cfg(feature = "arbitrary_precision")]
+impl Number {
+    // Returns the value if it is whole, the result can be huge for numbers like `1e1000000`
+    pub fn as_bigint(&self) -> Option<BigInt> {
+        let decimal = self.as_decimal();
+        let (negative, digits, exponent) = decimal.normalize();
+
+        if exponent < 0 {
+            return None;
+        }
+
+        let magnitude = BigInt::parse_bytes(digits.as_bytes(), 10)?
+            * BigInt::from(10).pow(exponent.try_into().ok()?);
+
+        Some(if negative { -magnitude } else { magnitude })
+    }
+
+    pub fn as_bigdecimal(&self) -> BigDecimal {
+        let decimal = self.as_decimal();
+        let digits = BigInt::parse_bytes(decimal.digits.as_bytes(), 10).unwrap_or_default();
+        let digits = if decimal.negative { -digits } else { digits };
+
+        BigDecimal::new(digits, decimal.exponent.saturating_neg())
+    }
+
+    // Rounds to `scale` digits after the decimal point, ties go to the even neighbour
+    pub fn with_scale(&self, scale: i64) -> Number {
+        self.as_bigdecimal()
+            .with_scale_round(scale, RoundingMode::HalfEven)
+            .into()
+    }
+}
+
+impl FromStr for Number {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let mut tokenizer = Tokenizer::new(s, TokenizeOptions::default());
+
+        match tokenizer.next().transpose()? {
+            Some(token) if token.type_ == TokenType::Number && token.len() == s.len() => {
+                Ok(Self::from_lexeme(s))
+            }
+            _ => {
+                let message = format!("Cannot parse `{}` as number", s);
+                Err(Error::Parse(ParseError::new(
+                    Span::new(s, 0, s.len()),
+                    message,
+                )))
+            }
+        }
+    }
+}
+
+// Numbers are equal when their values are, so `1.0` equals `1` and `1e2` equals `100`
+impl PartialEq for Number {
+    fn eq(&self, other: &Self) -> bool {
+        self.lexeme == other.lexeme || self.as_decimal() == other.as_decimal()
+    }
+}
+
+impl fmt::Debug for Number {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Number({})", self.lexeme)
+    }
+}
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.lexeme)
+    }
+}
+
+// Orders by value like `PartialEq`, so `1.0` is neither less nor greater than `1`
+impl PartialOrd for Number {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.as_decimal().cmp(&other.as_decimal()))
+    }
+}
+
+#[cfg(feature = "arbitrary_precision")]
+impl From<BigInt> for Number {
+    fn from(integer: BigInt) -> Self {
+        Self {
+            lexeme: integer.to_string(),
+        }
+    }
+}
+
+#[cfg(feature = "arbitrary_precision")]
+impl From<BigDecimal> for Number {
+    fn from(decimal: BigDecimal) -> Self {
+        let (digits, scale) = decimal.into_bigint_and_exponent();
+
+        // Goes through `Decimal` to always get valid JSON
+        let exponent = scale.saturating_neg();
+        let decimal = Decimal {
+            negative: digits.sign() == Sign::Minus,
+            digits: digits.magnitude().to_string(),
+            exponent,
+            exact: (exponent.unsigned_abs() > EXPONENT_LIMIT as u64)
+                .then(|| BigExponent::new(scale > 0, &scale.unsigned_abs().to_string())),
+        };
+
+        Self {
+            lexeme: decimal.to_string(),
+        }
+    }
+}
+
+// Exact arithmetic, `&a + &b` never loses precision
+macro_rules! number_operator {
+    ($($trait:ident $method:ident)*) => {
+    $(
+        #[cfg(feature = "arbitrary_precision")]
+        impl $trait for &Number {
+            type Output = Number;
+
+            fn $method(self, other: &Number) -> Number {
+                self.as_bigdecimal().$method(other.as_bigdecimal()).into()
+            }
+        }
+    )*
+    }
+}
+
+number_operator! { Add add Sub sub Mul mul }
+
+macro_rules! number_from_integer {
+    ($($type:ty)*) => {
+    $(
+        impl From<$type> for Number {
+            fn from(integer: $type) -> Self {
+                Self {
+                    lexeme: integer.to_string(),
+                }
+            }
+        }
+    )*
+    }
+}
+
+number_from_integer! { i8 i16 i32 i64 i128 isize u8 u16 u32 u64 u128 usize }
+
+// Exponents beyond this are also kept as a `BigExponent`. Below it, adding the number of digits
+// to an exponent can't overflow, because no input has that many digits.
+const EXPONENT_LIMIT: i64 = 1 << 62;
+
+// An arbitrary precision decimal with the value `digits * 10^exponent`, negated if `negative`
+#[derive(Debug, Clone)]
+pub struct Decimal {
+    negative: bool,
+    // ASCII digits without leading zeros, `"0"` for zero
+    digits: String,
+    // Saturates at the `i64` range, `exact` has the real value when it is beyond `EXPONENT_LIMIT`
+    exponent: i64,
+    exact: Option<BigExponent>,
+}
+
+impl Decimal {
+    fn from_lexeme(lexeme: &str) -> Self {
+        let (negative, unsigned) = match lexeme.strip_prefix('-') {
+            Some(unsigned) => (true, unsigned),
+            None => (false, lexeme),
+        };
+
+        let (mantissa, exponent) = match unsigned.find(['e', 'E']) {
+            Some(index) => (&unsigned[..index], &unsigned[index + 1..]),
+            None => (unsigned, ""),
+        };
+
+        let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+
+        let digits = format!("{}{}", integer, fraction);
+        let digits = match digits.trim_start_matches('0') {
+            "" => "0".to_owned(),
+            trimmed => trimmed.to_owned(),
+        };
+
+        let (exponent_negative, exponent_digits) = match exponent.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, exponent.trim_start_matches('+')),
+        };
+
+        let exponent = exponent_digits.bytes().fold(0i64, |exponent, digit| {
+            let digit = (digit - b'0') as i64;
+
+            if exponent_negative {
+                exponent.saturating_mul(10).saturating_sub(digit)
+            } else {
+                exponent.saturating_mul(10).saturating_add(digit)
+            }
+        });
+
+        let fraction_len = fraction.len() as i64;
+        let exact = (exponent.unsigned_abs() > EXPONENT_LIMIT as u64)
+            .then(|| BigExponent::new(exponent_negative, exponent_digits).add(-fraction_len));
+
+        Self {
+            negative,
+            digits,
+            exponent: exponent.saturating_sub(fraction_len),
+            exact,
+        }
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    pub fn digits(&self) -> &str {
+        &self.digits
+    }
+
+    // Saturates for exponents beyond the range of `i64`
+    pub fn exponent(&self) -> i64 {
+        self.exponent
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.digits == "0"
+    }
+
+    // Removes trailing zeros from the digits, so equal values have equal parts
+    fn normalize(&self) -> (bool, &str, i64) {
+        if self.is_zero() {
+            return (false, "0", 0);
+        }
+
+        let digits = self.digits.trim_end_matches('0');
+        let zeros = (self.digits.len() - digits.len()) as i64;
+
+        (self.negative, digits, self.exponent.saturating_add(zeros))
+    }
+
+    // Like `normalize`, with the exact exponent even if it doesn't fit in `i64`
+    fn normalize_exact(&self) -> (bool, &str, BigExponent) {
+        let (negative, digits, exponent) = self.normalize();
+
+        let exponent = match &self.exact {
+            Some(exact) => exact.add((self.digits.len() - digits.len()) as i64),
+            None => BigExponent::from(exponent),
+        };
+
+        (negative, digits, exponent)
+    }
+
+    fn to_i128(&self) -> Option<i128> {
+        let (negative, digits, exponent) = self.normalize();
+
+        if exponent < 0 {
+            return None;
+        }
+
+        let magnitude = digits
+            .parse::<u128>()
+            .ok()?
+            .checked_mul(10u128.checked_pow(exponent.try_into().ok()?)?)?;
+
+        if negative {
+            0i128.checked_sub_unsigned(magnitude)
+        } else {
+            magnitude.try_into().ok()
+        }
+    }
+}
+
+impl PartialEq for Decimal {
+    fn eq(&self, other: &Self) -> bool {
+        if self.exact.is_none() && other.exact.is_none() {
+            return self.normalize() == other.normalize();
+        }
+
+        self.normalize_exact() == other.normalize_exact()
+    }
+}
+
+impl Eq for Decimal {}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (negative, digits, exponent) = self.normalize();
+        let (other_negative, other_digits, other_exponent) = other.normalize();
+
+        // Position of the first digit
+        let position = |decimal: &Decimal, digits: &str, exponent: i64| match &decimal.exact {
+            Some(_) => decimal.normalize_exact().2.add(digits.len() as i64),
+            None => BigExponent::from(exponent + digits.len() as i64),
+        };
+
+        let magnitude = || match (digits, other_digits) {
+            ("0", "0") => Ordering::Equal,
+            ("0", _) => Ordering::Less,
+            (_, "0") => Ordering::Greater,
+            // Compare the position of the first digit, then the digits themselves,
+            // which works because normalized digits have no trailing zeros
+            _ if self.exact.is_none() && other.exact.is_none() => (digits.len() as i64 + exponent)
+                .cmp(&(other_digits.len() as i64 + other_exponent))
+                .then_with(|| digits.cmp(other_digits)),
+            _ => position(self, digits, exponent)
+                .cmp(&position(other, other_digits, other_exponent))
+                .then_with(|| digits.cmp(other_digits)),
+        };
+
+        match (negative, other_negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => magnitude(),
+            (true, true) => magnitude().reverse(),
+        }
+    }
+}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Formats like the general decimal arithmetic specification's to-scientific-string,
+// which always produces a valid JSON number
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.negative {
+            f.write_str("-")?;
+        }
+
+        let len = self.digits.len() as i64;
+
+        if let Some(exact) = &self.exact {
+            let (first, rest) = self.digits.split_at(1);
+            f.write_str(first)?;
+
+            if !rest.is_empty() {
+                write!(f, ".{}", rest)?;
+            }
+
+            let adjusted = exact.add(len - 1);
+            let sign = if adjusted.negative { "-" } else { "+" };
+            return write!(f, "e{}{}", sign, adjusted.digits);
+        }
+
+        let adjusted = self.exponent + len - 1;
+
+        if self.exponent <= 0 && adjusted >= -6 {
+            let point = len + self.exponent;
+
+            if point > 0 {
+                let (integer, fraction) = self.digits.split_at(point as usize);
+                f.write_str(integer)?;
+
+                if !fraction.is_empty() {
+                    write!(f, ".{}", fraction)?;
+                }
+            } else {
+                write!(f, "0.{}{}", "0".repeat(-point as usize), self.digits)?;
+            }
+
+            return Ok(());
+        }
+
+        let (first, rest) = self.digits.split_at(1);
+        f.write_str(first)?;
+
+        if !rest.is_empty() {
+            write!(f, ".{}", rest)?;
+        }
+
+        write!(
+            f,
+            "e{}{}",
+            if adjusted < 0 { "-" } else { "+" },
+            adjusted.unsigned_abs()
+        )
+    }
+}
+
+// An integer of any size, for exponents that don't fit in `i64`
+#[derive(Debug, PartialEq, Eq, Clone)]
+struct BigExponent {
+    negative: bool,
+    // ASCII digits without leading zeros, `"0"` for zero
+    digits: String,
+}
+
+impl BigExponent {
+    fn new(negative: bool, digits: &str) -> Self {
+        let digits = match digits.trim_start_matches('0') {
+            "" => return Self::from(0),
+            trimmed => trimmed.to_owned(),
+        };
+
+        Self { negative, digits }
+    }
+
+    fn add(&self, other: i64) -> Self {
+        let other = Self::from(other);
+
+        if self.negative == other.negative {
+            return Self::new(self.negative, &add_digits(&self.digits, &other.digits));
+        }
+
+        match compare_digits(&self.digits, &other.digits) {
+            Ordering::Less => Self::new(other.negative, &sub_digits(&other.digits, &self.digits)),
+            _ => Self::new(self.negative, &sub_digits(&self.digits, &other.digits)),
+        }
+    }
+}
+
+impl From<i64> for BigExponent {
+    fn from(integer: i64) -> Self {
+        Self {
+            negative: integer < 0,
+            digits: integer.unsigned_abs().to_string(),
+        }
+    }
+}
+
+impl Ord for BigExponent {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => compare_digits(&self.digits, &other.digits),
+            (true, true) => compare_digits(&self.digits, &other.digits).reverse(),
+        }
+    }
+}
+
+impl PartialOrd for BigExponent {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Compares digit strings without leading zeros
+fn compare_digits(a: &str, b: &str) -> Ordering {
+    a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+}
+
+fn add_digits(a: &str, b: &str) -> String {
+    let mut result = vec![];
+    let mut carry = 0;
+    let mut a = a.bytes().rev();
+    let mut b = b.bytes().rev();
+
+    loop {
+        let (x, y) = (a.next(), b.next());
+        if x.is_none() && y.is_none() && carry == 0 {
+            break;
+        }
+
+        let sum = x.map_or(0, |x| x - b'0') + y.map_or(0, |y| y - b'0') + carry;
+        result.push(b'0' + sum % 10);
+        carry = sum / 10;
+    }
+
+    result.iter().rev().map(|&digit| digit as char).collect()
+}
+
+// `a` must not be smaller than `b`
+fn sub_digits(a: &str, b: &str) -> String {
+    let mut result = vec![];
+    let mut borrow = 0;
+    let mut b = b.bytes().rev();
+
+    for x in a.bytes().rev() {
+        let y = b.next().map_or(0, |y| y - b'0') + borrow;
+        let x = x - b'0';
+
+        borrow = (x < y) as u8;
+        result.push(b'0' + x + borrow * 10 - y);
+    }
+
+    result.iter().rev().map(|&digit| digit as char).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "arbitrary_precision")]
+    use bigdecimal::BigDecimal;
+    #[cfg(feature = "arbitrary_precision")]
+    use num_bigint::BigInt;
+
+    use std::cmp::Ordering;
+
+    use crate::number::Number;
+
+    fn number(lexeme: &str) -> Number {
+        lexeme.parse().unwrap()
+    }
+
+    macro_rules! integer_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (lexeme, expected_i64, expected_u64, expected_i128): (&str, Option<i64>, Option<u64>, Option<i128>) = $value;
+                let number = number(lexeme);
+
+                assert_eq!(number.as_i64(), expected_i64);
+                assert_eq!(number.as_u64(), expected_u64);
+                assert_eq!(number.as_i128(), expected_i128);
+            }
+        )*
+        }
+    }
+
+    integer_tests! {
+        test_integer_small: ("42", Some(42), Some(42), Some(42)),
+        test_integer_negative: ("-42", Some(-42), None, Some(-42)),
+        test_integer_zero_fraction: ("-0.0", Some(0), Some(0), Some(0)),
+        test_integer_above_f64_precision: (
+                "9007199254740993",
+                Some(9007199254740993),
+                Some(9007199254740993),
+                Some(9007199254740993),
+            ),
+        test_integer_u64_max: ("18446744073709551615", None, Some(u64::MAX), Some(u64::MAX as i128)),
+        test_integer_i64_min: ("-9223372036854775808", Some(i64::MIN), None, Some(i64::MIN as i128)),
+        test_integer_i128_min: (
+                "-170141183460469231731687303715884105728",
+                None,
+                None,
+                Some(i128::MIN),
+            ),
+        test_integer_too_large: ("1e39", None, None, None),
+        test_integer_exponent: ("1.5e3", Some(1500), Some(1500), Some(1500)),
+        test_integer_trailing_zeros: ("100.00", Some(100), Some(100), Some(100)),
+        test_integer_fraction: ("1.5", None, None, None),
+        test_integer_huge_exponent: ("1e99999999999999999999", None, None, None),
+        test_integer_tiny_exponent: ("1e-99999999999999999999", None, None, None),
+    }
+
+    macro_rules! decimal_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (lexeme, expected) = $value;
+                assert_eq!(number(lexeme).as_decimal().to_string(), expected);
+            }
+        )*
+        }
+    }
+
+    decimal_tests! {
+        test_decimal_integer: ("100", "100"),
+        test_decimal_fraction: ("-12.50", "-12.50"),
+        test_decimal_small_fraction: ("0.000001", "0.000001"),
+        test_decimal_tiny_fraction: ("0.0000001", "1e-7"),
+        test_decimal_exponent: ("1E2", "1e+2"),
+        test_decimal_negative_exponent: ("12e-1", "1.2"),
+        test_decimal_many_digits: (
+                "3.14159265358979323846264338327950288419716939937510",
+                "3.14159265358979323846264338327950288419716939937510",
+            ),
+        test_decimal_large_exponent: ("-2.5e400", "-2.5e+400"),
+        test_decimal_leading_zeros: ("0.05", "0.05"),
+    }
+
+    #[test]
+    fn test_decimal_parts() {
+        let decimal = number("-0.0123400e3").as_decimal();
+
+        assert!(decimal.is_negative());
+        assert_eq!(decimal.digits(), "123400");
+        assert_eq!(decimal.exponent(), -4);
+        assert!(number("-0.000").as_decimal().is_zero());
+    }
+
+    #[test]
+    fn test_decimal_ordering() {
+        let ordered = [
+            "-1e400",
+            "-12",
+            "-1.5",
+            "-0.000",
+            "1e-400",
+            "0.3",
+            "0.30000000000000000001",
+            "1.0",
+            "1.05",
+            "10",
+            "9007199254740993",
+        ];
+
+        for pair in ordered.windows(2) {
+            let (smaller, larger) = (number(pair[0]).as_decimal(), number(pair[1]).as_decimal());
+            assert!(smaller < larger, "{} >= {}", pair[0], pair[1]);
+            assert!(larger > smaller, "{} <= {}", pair[1], pair[0]);
+        }
+        assert_eq!(
+            number("1.0").as_decimal().cmp(&number("1").as_decimal()),
+            Ordering::Equal
+        );
+        assert_eq!(
+            number("-0").as_decimal().cmp(&number("0e5").as_decimal()),
+            Ordering::Equal
+        );
+    }
+
+    #[test]
+    fn test_as_f64() {
+        assert_eq!(number("-13.37").as_f64(), Some(-13.37));
+        assert_eq!(
+            number("9007199254740993").as_f64(),
+            Some(9007199254740992f64)
+        );
+        assert_eq!(number("1e400").as_f64(), None);
+    }
+
+    #[test]
+    fn test_eq_compares_values() {
+        assert_eq!(number("1"), number("1.0"));
+        assert_eq!(number("100"), number("1E2"));
+        assert_eq!(number("0.5"), number("5e-1"));
+        assert_eq!(number("0"), number("-0.0"));
+        assert_ne!(number("1"), number("-1"));
+        assert_ne!(number("9007199254740993"), number("9007199254740992"));
+    }
+
+    #[test]
+    fn test_huge_exponents_are_exact() {
+        assert_ne!(
+            number("1e99999999999999999999"),
+            number("1e99999999999999999998")
+        );
+        assert_eq!(
+            number("10e99999999999999999998"),
+            number("1e99999999999999999999")
+        );
+        assert_eq!(
+            number("0.01e-99999999999999999998"),
+            number("1E-100000000000000000000")
+        );
+        assert_ne!(
+            number("1e9223372036854775807"),
+            number("10e9223372036854775807")
+        );
+
+        let ordered = [
+            "-1e99999999999999999999",
+            "-1e99999999999999999998",
+            "1e-99999999999999999999",
+            "1e-99999999999999999998",
+            "1",
+            "9.99e99999999999999999998",
+            "1e99999999999999999999",
+            "1.5e99999999999999999999",
+        ];
+
+        for pair in ordered.windows(2) {
+            let (smaller, larger) = (number(pair[0]).as_decimal(), number(pair[1]).as_decimal());
+            assert!(smaller < larger, "{} >= {}", pair[0], pair[1]);
+        }
+        assert_eq!(
+            number("-25e99999999999999999999").as_decimal().to_string(),
+            "-2.5e+100000000000000000000"
+        );
+    }
+
+    #[test]
+    fn test_from_integer() {
+        assert_eq!(Number::from(u64::MAX).as_str(), "18446744073709551615");
+        assert_eq!(Number::from(-7i32).as_str(), "-7");
+    }
+
+    macro_rules! from_f64_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (float, expected): (f64, Option<&str>) = $value;
+                assert_eq!(Number::from_f64(float).as_ref().map(Number::as_str), expected);
+            }
+        )*
+        }
+    }
+
+    from_f64_tests! {
+        test_from_f64_integer: (3f64, Some("3")),
+        test_from_f64_decimal: (-13.37f64, Some("-13.37")),
+        test_from_f64_negative_zero: (-0f64, Some("-0")),
+        test_from_f64_large_integer: (9007199254740993f64, Some("9007199254740992")),
+        test_from_f64_large_exponent: (1e300f64, Some("1e300")),
+        test_from_f64_small_exponent: (-1.5e-7f64, Some("-1.5e-7")),
+        test_from_f64_small_decimal: (0.001f64, Some("0.001")),
+        test_from_f64_shortest_digits: (0.1f64 + 0.2f64, Some("0.30000000000000004")),
+        test_from_f64_nan: (f64::NAN, None),
+        test_from_f64_infinity: (f64::INFINITY, None),
+    }
+
+    #[test]
+    fn test_from_str_error() {
+        let error = "01".parse::<Number>().unwrap_err();
+        assert_eq!(error.message(), "Leading zeros are not allowed in numbers");
+
+        let error = "1 2".parse::<Number>().unwrap_err();
+        assert_eq!(error.message(), "Cannot parse `1 2` as number");
+
+        let error = "\"1\"".parse::<Number>().unwrap_err();
+        assert_eq!(error.message(), "Cannot parse `\"1\"` as number");
+    }
+
+    #[cfg(feature = "arbitrary_precision")]
+    #[test]
+    fn test_as_bigint() {
+        let expected: BigInt = "-1234567890123456789012345678900000".parse().unwrap();
+
+        assert_eq!(
+            number("-1234567890123456789012345678900e3").as_bigint(),
+            Some(expected)
+        );
+        assert_eq!(number("1.5").as_bigint(), None);
+        assert_eq!(number("2.000").as_bigint(), Some(BigInt::from(2)));
+    }
+
+    #[cfg(feature = "arbitrary_precision")]
+    #[test]
+    fn test_bigint_round_trip() {
+        let integer = BigInt::from(u128::MAX) * BigInt::from(u128::MAX);
+        assert_eq!(Number::from(integer.clone()).as_bigint(), Some(integer));
+    }
+
+    #[cfg(feature = "arbitrary_precision")]
+    #[test]
+    fn test_as_bigdecimal() {
+        let expected: BigDecimal = "-0.000012345".parse().unwrap();
+        assert_eq!(number("-1.2345e-5").as_bigdecimal(), expected);
+    }
+
+    #[cfg(feature = "arbitrary_precision")]
+    macro_rules! arithmetic_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (result, expected): (Number, &str) = $value;
+                assert_eq!(result.as_str(), expected);
+            }
+        )*
+        }
+    }
+
+    #[cfg(feature = "arbitrary_precision")]
+    arithmetic_tests! {
+        test_add_decimals: (&number("0.1") + &number("0.2"), "0.3"),
+        test_add_keeps_scale: (&number("19.99") + &number("0.01"), "20.00"),
+        test_add_large_integers: (
+                &number("18446744073709551615") + &number("18446744073709551615"),
+                "36893488147419103230",
+            ),
+        test_sub_below_zero: (&number("1.05") - &number("2"), "-0.95"),
+        test_mul_scaling: (&number("12.34") * &number("100"), "1234.00"),
+        test_mul_fraction: (&number("-2.5") * &number("0.04"), "-0.100"),
+        test_with_scale_rounds_half_even: (number("2.345").with_scale(2), "2.34"),
+        test_with_scale_rounds_up: (number("2.3451").with_scale(2), "2.35"),
+        test_with_scale_pads: (number("7").with_scale(2), "7.00"),
+        test_from_bigdecimal_exponent: (Number::from("1e30".parse::<BigDecimal>().unwrap()), "1e+30"),
+    }
+
+    #[test]
+    fn test_exact_comparison() {
+        assert!(number("9007199254740993") > number("9007199254740992"));
+        assert!(number("-1e400") < number("1e-400"));
+        assert!(number("0.30000000000000000001") > number("0.3"));
+        assert!(number("1e99999999999999999999") > number("1e99999999999999999998"));
+        assert_eq!(
+            number("1.0").partial_cmp(&number("1")),
+            Some(Ordering::Equal)
+        );
+    }
+}
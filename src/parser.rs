@@ -1,285 +1,363 @@
-use std::collections::HashMap;
-
-use crate::tokenizer::{Token, TokenType};
-
-#[derive(Debug, PartialEq)]
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::mem;
+
+use crate::borrowed::BorrowedJson;
+use crate::error::Error;
+use crate::events::{Event, EventReader};
+use crate::map::Map;
+use crate::number::Number;
+use crate::span::Span;
+use crate::tokenizer::TokenizeOptions;
+
+#[derive(Debug, PartialEq, Clone)]
 pub enum Json {
     Null,
     Boolean(bool),
-    Number(f64),
+    Number(Number),
     String(String),
     Array(Vec<Json>),
-    Object(HashMap<String, Json>),
+    Object(Map),
 }
 
 #[derive(Debug, PartialEq)]
 pub struct ParseError {
-    pub offset: usize,
+    pub span: Span,
     pub message: String,
 }
 
 impl ParseError {
-    fn new(offset: usize, message: String) -> Self {
-        Self { offset, message }
+    pub(crate) fn new(span: Span, message: String) -> Self {
+        Self { span, message }
     }
 }
 
-struct Parser {
-    tokens: Vec<Token>,
-    offset: usize,
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.span, self.message)
+    }
 }
 
-impl Parser {
-    fn new(tokens: Vec<Token>, offset: usize) -> Self {
-        Self { tokens, offset }
+impl std::error::Error for ParseError {}
+
+// A key that occurs twice in the same object, rejected with `DuplicateKeys::Error`
+#[derive(Debug, PartialEq)]
+pub struct DuplicateKeyError {
+    pub span: Span,
+    // Span of the earlier occurrence of the key
+    pub first: Span,
+    pub message: String,
+}
+
+impl DuplicateKeyError {
+    fn new(span: Span, first: Span, message: String) -> Self {
+        Self {
+            span,
+            first,
+            message,
+        }
     }
+}
+
+impl fmt::Display for DuplicateKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} (first defined at {})",
+            self.span, self.message, self.first
+        )
+    }
+}
+
+impl std::error::Error for DuplicateKeyError {}
+
+// What to do when an object contains the same key more than once
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum DuplicateKeys {
+    // Fail with `Error::DuplicateKey`
+    Error,
+    // Keep the value of the first occurrence
+    FirstWins,
+    // Keep the value of the last occurrence
+    LastWins,
+    // Keep all values, in order, in an array
+    Collect,
+}
 
-    fn parse(&mut self) -> Result<Json, ParseError> {
-        self.tokens.retain(|x| x.type_ != TokenType::Whitespace);
-        let parsed = self._parse()?;
+#[derive(Debug, PartialEq, Clone)]
+pub struct ParseOptions {
+    // Reject raw control characters and unknown escape sequences inside strings
+    pub strict: bool,
+    pub duplicate_keys: DuplicateKeys,
+    // Arrays and objects nested deeper than this are rejected, so deep input can't overflow the stack
+    pub max_depth: usize,
+}
 
-        if self.offset < self.tokens.len() {
-            return Err(ParseError::new(
-                self.offset,
-                "Unexpected extra input found".to_owned(),
-            ));
+impl ParseOptions {
+    pub(crate) fn tokenize_options(&self) -> TokenizeOptions {
+        TokenizeOptions {
+            strict: self.strict,
         }
+    }
+}
 
-        Ok(parsed)
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            strict: true,
+            duplicate_keys: DuplicateKeys::LastWins,
+            max_depth: 128,
+        }
     }
+}
 
-    fn parse_string(&mut self) -> Result<Json, ParseError> {
-        let s = self.parse_string_key()?;
-        Ok(Json::String(s))
+// A value tree the parser can build, implemented by `Json` and `BorrowedJson`
+pub(crate) trait Tree<'a>: Sized {
+    // Entries of an object while it is being parsed
+    type Object: Default;
+
+    fn null() -> Self;
+    fn boolean(value: bool) -> Self;
+    fn number(lexeme: &'a str) -> Self;
+    fn string(value: Cow<'a, str>) -> Self;
+    fn array(values: Vec<Self>) -> Self;
+    fn object(object: Self::Object) -> Self;
+
+    fn get_mut<'o>(object: &'o mut Self::Object, key: &str) -> Option<&'o mut Self>;
+    // Only called for keys that are not in `object` yet
+    fn insert(object: &mut Self::Object, key: Cow<'a, str>, value: Self);
+    fn as_array_mut(&mut self) -> Option<&mut Vec<Self>>;
+}
+
+impl<'a> Tree<'a> for Json {
+    type Object = Map;
+
+    fn null() -> Self {
+        Json::Null
     }
 
-    fn parse_string_key(&mut self) -> Result<String, ParseError> {
-        let token = &self.tokens.get(self.offset);
+    fn boolean(value: bool) -> Self {
+        Json::Boolean(value)
+    }
 
-        match token {
-            None => Err(ParseError::new(
-                self.offset,
-                "Unexpected end of input".to_owned(),
-            )),
-            Some(token) => {
-                if token.type_ != TokenType::String {
-                    return Err(ParseError::new(
-                        self.offset,
-                        format!("Cannot parse `{}` as string", token.value),
-                    ));
-                }
+    // The tokenizer only produces valid numbers, so the lexeme is kept as is
+    fn number(lexeme: &'a str) -> Self {
+        Json::Number(Number::from_lexeme(lexeme))
+    }
 
-                self.offset += 1;
-                let s = token.value[1..token.value.len() - 1].to_owned();
-                Ok(s)
-            }
-        }
+    fn string(value: Cow<'a, str>) -> Self {
+        Json::String(value.into_owned())
     }
 
-    fn parse_array(&mut self) -> Result<Json, ParseError> {
-        let mut array: Vec<Json> = vec![];
+    fn array(values: Vec<Self>) -> Self {
+        Json::Array(values)
+    }
 
-        // Consume `[` chracter
-        self.offset += 1;
+    fn object(object: Map) -> Self {
+        Json::Object(object)
+    }
 
-        let token = &self.tokens.get(self.offset);
-        match token {
-            None => {
-                return Err(ParseError::new(
-                    self.offset,
-                    "Unexpected end of input".to_owned(),
-                ))
-            }
-            Some(token) => {
-                if token.type_ == TokenType::ArrayEnd {
-                    // Found empty array
-                    self.offset += 1;
-                    return Ok(Json::Array(array));
-                }
-            }
+    fn get_mut<'o>(object: &'o mut Map, key: &str) -> Option<&'o mut Self> {
+        object.get_mut(key)
+    }
+
+    fn insert(object: &mut Map, key: Cow<'a, str>, value: Self) {
+        object.insert(key.into_owned(), value);
+    }
+
+    fn as_array_mut(&mut self) -> Option<&mut Vec<Self>> {
+        match self {
+            Json::Array(values) => Some(values),
+            _ => None,
         }
+    }
+}
 
-        loop {
-            let item = self._parse()?;
-            array.push(item);
+pub(crate) struct Parser<'a> {
+    events: EventReader<'a>,
+    duplicate_keys: DuplicateKeys,
+    max_depth: usize,
+    // Number of arrays and objects that contain the value being parsed
+    depth: usize,
+}
 
-            let token = &self.tokens.get(self.offset);
+impl<'a> Parser<'a> {
+    fn new(input: &'a str, options: ParseOptions) -> Self {
+        Self {
+            events: EventReader::new(input, options.tokenize_options()),
+            duplicate_keys: options.duplicate_keys,
+            max_depth: options.max_depth,
+            depth: 0,
+        }
+    }
 
-            match token {
-                None => {
-                    return Err(ParseError::new(
-                        self.offset,
-                        "Unexpected end of input".to_owned(),
-                    ))
-                }
-                Some(token) => match token.type_ {
-                    TokenType::Comma => self.offset += 1,
-                    TokenType::ArrayEnd => {
-                        self.offset += 1;
-                        break;
-                    }
-                    _ => {
-                        return Err(ParseError::new(
-                            self.offset,
-                            format!("Unexpected token `{}` in array", token.value),
-                        ))
-                    }
-                },
-            }
+    // Parses values that follow each other with `parse_next`
+    pub(crate) fn concatenated(input: &'a str, options: ParseOptions) -> Self {
+        Self {
+            events: EventReader::concatenated(input, options.tokenize_options()),
+            duplicate_keys: options.duplicate_keys,
+            max_depth: options.max_depth,
+            depth: 0,
         }
+    }
 
-        Ok(Json::Array(array))
+    // The event reader checks the structure, so events always arrive in a valid order
+    fn next_event(&mut self) -> Result<Event<'a>, Error> {
+        Ok(self
+            .events
+            .next_event()?
+            .expect("event reader ended inside a value"))
     }
 
-    fn parse_object(&mut self) -> Result<Json, ParseError> {
-        let mut object: HashMap<String, Json> = HashMap::new();
+    fn parse<T: Tree<'a>>(&mut self) -> Result<T, Error> {
+        let event = self.next_event()?;
+        let parsed = self.parse_value(event)?;
 
-        // Consume `{` chracter
-        self.offset += 1;
+        // Fails if there is extra input after the value
+        self.events.next_event()?;
 
-        let token = &self.tokens.get(self.offset);
+        Ok(parsed)
+    }
 
-        match token {
-            None => {
-                return Err(ParseError::new(
-                    self.offset,
-                    "Unexpected end of input".to_owned(),
-                ))
-            }
-            Some(token) => {
-                if token.type_ == TokenType::ObjectEnd {
-                    // Found empty object
-                    self.offset += 1;
-                    return Ok(Json::Object(object));
-                }
-            }
+    // Returns `None` once all concatenated values were parsed
+    pub(crate) fn parse_next<T: Tree<'a>>(&mut self) -> Result<Option<T>, Error> {
+        match self.events.next_event()? {
+            None => Ok(None),
+            Some(event) => self.parse_value(event).map(Some),
         }
+    }
 
-        loop {
-            let key = self.parse_string_key()?;
-
-            let token = &self.tokens.get(self.offset);
-
-            match token {
-                None => {
-                    return Err(ParseError::new(
-                        self.offset,
-                        "Unexpected end of input".to_owned(),
-                    ))
+    fn parse_value<T: Tree<'a>>(&mut self, event: Event<'a>) -> Result<T, Error> {
+        match event {
+            Event::Value(value) => Ok(match value {
+                BorrowedJson::Null => T::null(),
+                BorrowedJson::Boolean(value) => T::boolean(value),
+                BorrowedJson::Number(lexeme) => T::number(lexeme),
+                BorrowedJson::String(value) => T::string(value),
+                _ => unreachable!("value events only contain scalars"),
+            }),
+            Event::StartArray | Event::StartObject => {
+                if self.depth == self.max_depth {
+                    let (offset, len) = self.events.position();
+                    let span = Span::new(self.events.input(), offset, len);
+                    let message =
+                        format!("Nesting is deeper than the maximum of {}", self.max_depth);
+                    return Err(Error::Parse(ParseError::new(span, message)));
                 }
-                Some(token) => match token.type_ {
-                    TokenType::Colon => {
-                        self.offset += 1;
 
-                        let value = self._parse()?;
-                        object.insert(key, value);
-                    }
-                    _ => {
-                        return Err(ParseError::new(
-                            self.offset,
-                            format!("Unexpected token `{}` in object", token.value),
-                        ))
-                    }
-                },
+                self.depth += 1;
+                let parsed = match event {
+                    Event::StartArray => self.parse_array(),
+                    _ => self.parse_object(),
+                };
+                self.depth -= 1;
+
+                parsed
             }
+            _ => unreachable!("event reader produced {:?} instead of a value", event),
+        }
+    }
 
-            let token = &self.tokens.get(self.offset);
+    fn parse_array<T: Tree<'a>>(&mut self) -> Result<T, Error> {
+        let mut array: Vec<T> = vec![];
 
-            match token {
-                None => {
-                    return Err(ParseError::new(
-                        self.offset,
-                        "Unexpected end of input".to_owned(),
-                    ))
-                }
-                Some(token) => match token.type_ {
-                    TokenType::Comma => self.offset += 1,
-                    TokenType::ObjectEnd => {
-                        self.offset += 1;
-                        break;
-                    }
-                    _ => {
-                        return Err(ParseError::new(
-                            self.offset,
-                            format!("Unexpected token `{}` in object", token.value),
-                        ))
-                    }
-                },
+        loop {
+            match self.next_event()? {
+                Event::EndArray => return Ok(T::array(array)),
+                event => array.push(self.parse_value(event)?),
             }
         }
-
-        Ok(Json::Object(object))
     }
 
-    fn parse_number(&mut self) -> Result<Json, ParseError> {
-        let token = &self.tokens.get(self.offset);
-
-        match token {
-            None => Err(ParseError::new(
-                self.offset,
-                "Unexpected end of input".to_owned(),
-            )),
-            Some(token) => {
-                let parsed = token.value.parse::<f64>();
+    fn parse_object<T: Tree<'a>>(&mut self) -> Result<T, Error> {
+        let mut object = T::Object::default();
+        // Offset and length of each key token, only tracked to report duplicates
+        let mut key_positions: HashMap<Cow<'a, str>, (usize, usize)> = HashMap::new();
+        // Keys whose values were already gathered into an array
+        let mut collected: HashSet<Cow<'a, str>> = HashSet::new();
 
-                match parsed {
-                    Err(_) => Err(ParseError::new(
-                        self.offset,
-                        format!("Cannot parse `{}` as number", token.value),
-                    )),
-                    Ok(float) => {
-                        self.offset += 1;
-                        Ok(Json::Number(float))
-                    }
+        loop {
+            let key = match self.next_event()? {
+                Event::EndObject => return Ok(T::object(object)),
+                Event::Key(key) => key,
+                event => unreachable!("event reader produced {:?} instead of a key", event),
+            };
+
+            if self.duplicate_keys == DuplicateKeys::Error {
+                let position = self.events.position();
+
+                if let Some(&first) = key_positions.get(&key) {
+                    let input = self.events.input();
+                    let message = format!("Duplicate key `{}` in object", key);
+
+                    return Err(Error::DuplicateKey(DuplicateKeyError::new(
+                        Span::new(input, position.0, position.1),
+                        Span::new(input, first.0, first.1),
+                        message,
+                    )));
                 }
+                key_positions.insert(key.clone(), position);
             }
+
+            let event = self.next_event()?;
+            let value: T = self.parse_value(event)?;
+            self.insert(&mut object, &mut collected, key, value);
         }
     }
 
-    fn _parse(&mut self) -> Result<Json, ParseError> {
-        let token = &self.tokens.get(self.offset);
-
-        match token {
-            None => Err(ParseError::new(
-                self.offset,
-                "Unexpected end of input".to_owned(),
-            )),
-            Some(token) => match token.type_ {
-                TokenType::Null => {
-                    self.offset += 1;
-                    Ok(Json::Null)
-                }
-                TokenType::True => {
-                    self.offset += 1;
-                    Ok(Json::Boolean(true))
+    fn insert<T: Tree<'a>>(
+        &self,
+        object: &mut T::Object,
+        collected: &mut HashSet<Cow<'a, str>>,
+        key: Cow<'a, str>,
+        value: T,
+    ) {
+        match self.duplicate_keys {
+            DuplicateKeys::Error | DuplicateKeys::LastWins => match T::get_mut(object, &key) {
+                Some(existing) => *existing = value,
+                None => T::insert(object, key, value),
+            },
+            DuplicateKeys::FirstWins => {
+                if T::get_mut(object, &key).is_none() {
+                    T::insert(object, key, value);
                 }
-                TokenType::False => {
-                    self.offset += 1;
-                    Ok(Json::Boolean(false))
+            }
+            DuplicateKeys::Collect => match T::get_mut(object, &key) {
+                None => T::insert(object, key, value),
+                Some(existing) => {
+                    if collected.insert(key) {
+                        let first = mem::replace(existing, T::null());
+                        *existing = T::array(vec![first, value]);
+                    } else if let Some(values) = existing.as_array_mut() {
+                        values.push(value);
+                    }
                 }
-                TokenType::Number => self.parse_number(),
-                TokenType::String => self.parse_string(),
-                TokenType::ArrayStart => self.parse_array(),
-                TokenType::ObjectStart => self.parse_object(),
-                _ => Err(ParseError::new(
-                    self.offset,
-                    format!("Found unexpected token `{}`", token.value),
-                )),
             },
         }
     }
 }
 
-pub fn parse(tokens: Vec<Token>) -> Result<Json, ParseError> {
-    Parser::new(tokens, 0).parse()
+// Parses `input` in a single pass, pulling events from the event reader as they are needed
+pub(crate) fn parse_tree<'a, T: Tree<'a>>(
+    input: &'a str,
+    options: ParseOptions,
+) -> Result<T, Error> {
+    Parser::new(input, options).parse()
+}
+
+pub fn parse(input: &str, options: ParseOptions) -> Result<Json, Error> {
+    parse_tree(input, options)
 }
 
 #[cfg(test)]
 mod tests {
-    use std::collections::HashMap;
-
-    use crate::parser::{parse, Json, ParseError, Parser};
-    use crate::tokenizer::tokenize;
+    use crate::error::Error;
+    use crate::map::Map;
+    use crate::number::Number;
+    use crate::parser::{parse, DuplicateKeyError, DuplicateKeys, Json, ParseError, ParseOptions};
+    use crate::span::Span;
+    use crate::tokenizer::TokenizeError;
 
     macro_rules! parser_tests {
         ($($name:ident: $value:expr,)*) => {
@@ -287,8 +365,7 @@ mod tests {
             #[test]
             fn $name() {
                 let (input, expected) = $value;
-                let tokens = tokenize(input).unwrap();
-                let json = parse(tokens);
+                let json = parse(input, ParseOptions::default());
                 assert_eq!(json, expected);
 
             }
@@ -305,145 +382,323 @@ mod tests {
                 "\"hello world\"",
                 Ok(Json::String("hello world".to_owned())),
             ),
+        test_parse_string_escapes: (
+                r#""\"\\\/\b\f\n\r\t""#,
+                Ok(Json::String("\"\\/\u{8}\u{c}\n\r\t".to_owned())),
+            ),
+        test_parse_string_unicode_escape: (
+                r#""caf\u00e9 \u20AC""#,
+                Ok(Json::String("café €".to_owned())),
+            ),
+        test_parse_string_surrogate_pair: (
+                r#""\ud83d\ude00""#,
+                Ok(Json::String("😀".to_owned())),
+            ),
+        test_parse_dict_escaped_key: (
+                r#"{"a\"b": null}"#,
+                Ok(Json::Object(Map::from([("a\"b".to_owned(), Json::Null)]))),
+            ),
         test_parse_list_with_bool:    ("[false]", Ok(Json::Array(vec![Json::Boolean(false)]))),
         test_parse_list_with_null:    ("[null]", Ok(Json::Array(vec![Json::Null]))),
         test_parse_list_with_mixed:    (
                 "[1,2,3,false]",
                 Ok(Json::Array(vec![
-                    Json::Number(1f64),
-                    Json::Number(2f64),
-                    Json::Number(3f64),
+                    Json::Number(1.into()),
+                    Json::Number(2.into()),
+                    Json::Number(3.into()),
                     Json::Boolean(false),
                 ])),
             ),
         test_parse_list_nested: (
                 "[[1],null]",
                 Ok(Json::Array(vec![
-                    Json::Array(vec![Json::Number(1f64)]),
+                    Json::Array(vec![Json::Number(1.into())]),
                     Json::Null,
                 ])),
             ),
-        test_parse_dict_empty: ("{}", Ok(Json::Object(HashMap::new()))),
+        test_parse_dict_empty: ("{}", Ok(Json::Object(Map::new()))),
         test_parse_dict_one_item:           (
                 "{\"foo\": 1337}",
-                Ok(Json::Object(HashMap::from([(
+                Ok(Json::Object(Map::from([(
                     "foo".to_owned(),
-                    Json::Number(1337f64),
+                    Json::Number(1337.into()),
                 )]))),
             ),
         test_parse_dict_two_items: (
                 "{\"foo\": 13.37, \"bar\": [69]}",
-                Ok(Json::Object(HashMap::from([
-                    ("foo".to_owned(), Json::Number(13.37f64)),
-                    ("bar".to_owned(), Json::Array(vec![Json::Number(69f64)])),
+                Ok(Json::Object(Map::from([
+                    ("foo".to_owned(), Json::Number("13.37".parse().unwrap())),
+                    ("bar".to_owned(), Json::Array(vec![Json::Number(69.into())])),
                 ]))),
             ),
         test_parse_extra_input: (
                 "truefalse",
-                Err(ParseError {
-                    offset: 1,
+                Err(Error::Parse(ParseError {
+                    span: Span { offset: 4, len: 5, line: 1, column: 5 },
                     message: "Unexpected extra input found".to_owned(),
-                }),
+                })),
             ),
         test_parse_object_fail_1:    (
                 "{",
-                Err(ParseError {
-                    offset: 1,
+                Err(Error::Parse(ParseError {
+                    span: Span { offset: 1, len: 0, line: 1, column: 2 },
                     message: "Unexpected end of input".to_owned(),
-                }),
+                })),
             ),
             test_parse_object_fail_2:    (
                 "{\"some key\"",
-                Err(ParseError {
-                    offset: 2,
+                Err(Error::Parse(ParseError {
+                    span: Span { offset: 11, len: 0, line: 1, column: 12 },
                     message: "Unexpected end of input".to_owned(),
-                }),
+                })),
             ),
             test_parse_object_fail_3:    (
                 "{\"some key\":",
-                Err(ParseError {
-                    offset: 3,
+                Err(Error::Parse(ParseError {
+                    span: Span { offset: 12, len: 0, line: 1, column: 13 },
                     message: "Unexpected end of input".to_owned(),
-                }),
+                })),
             ),
             test_parse_object_fail_4:    (
                 "{\"some key\":\"some value\"",
-                Err(ParseError {
-                    offset: 4,
+                Err(Error::Parse(ParseError {
+                    span: Span { offset: 24, len: 0, line: 1, column: 25 },
                     message: "Unexpected end of input".to_owned(),
-                }),
+                })),
             ),
             test_parse_object_fail_5:    (
                 "{\"some key\":\"some value\" 3",
-                Err(ParseError {
-                    offset: 4,
+                Err(Error::Parse(ParseError {
+                    span: Span { offset: 25, len: 1, line: 1, column: 26 },
                     message: "Unexpected token `3` in object".to_owned(),
-                }),
+                })),
             ),
             test_parse_object_fail_6: (
                     "{3:\"some value\"",
-                Err(ParseError {
-                    offset: 1,
+                Err(Error::Parse(ParseError {
+                    span: Span { offset: 1, len: 1, line: 1, column: 2 },
                     message: "Cannot parse `3` as string".to_owned(),
-                }),
+                })),
             ),
             test_parse_object_fail_7:(
                 "{\"some key\" 3",
-                Err(ParseError {
-                    offset: 2,
+                Err(Error::Parse(ParseError {
+                    span: Span { offset: 12, len: 1, line: 1, column: 13 },
                     message: "Unexpected token `3` in object".to_owned(),
-                }),
+                })),
             ),
             test_parse_object_fail_8:(
                 "{3",
-                Err(ParseError {
-                    offset: 1,
+                Err(Error::Parse(ParseError {
+                    span: Span { offset: 1, len: 1, line: 1, column: 2 },
                     message: "Cannot parse `3` as string".to_owned(),
-                }),
+                })),
             ),
             test_parse_array_fail_1:(
                 "[",
-                Err(ParseError {
-                    offset: 1,
+                Err(Error::Parse(ParseError {
+                    span: Span { offset: 1, len: 0, line: 1, column: 2 },
                     message: "Unexpected end of input".to_owned(),
-                }),
+                })),
             ),
             test_parse_array_fail_2:(
                 "[3",
-                Err(ParseError {
-                    offset: 2,
+                Err(Error::Parse(ParseError {
+                    span: Span { offset: 2, len: 0, line: 1, column: 3 },
                     message: "Unexpected end of input".to_owned(),
-                }),
+                })),
             ),
             test_parse_array_fail_3: (
                 "[3,",
-                Err(ParseError {
-                    offset: 3,
+                Err(Error::Parse(ParseError {
+                    span: Span { offset: 3, len: 0, line: 1, column: 4 },
                     message: "Unexpected end of input".to_owned(),
-                }),
+                })),
             ),
             test_parse_array_fail_4:(
                 "[3 5",
-                Err(ParseError {
-                    offset: 2,
+                Err(Error::Parse(ParseError {
+                    span: Span { offset: 3, len: 1, line: 1, column: 4 },
                     message: "Unexpected token `5` in array".to_owned(),
-                }),
+                })),
             ),
             test_parse_empty: (
                 "",
-                Err(ParseError {
-                    offset: 0,
+                Err(Error::Parse(ParseError {
+                    span: Span { offset: 0, len: 0, line: 1, column: 1 },
                     message: "Unexpected end of input".to_owned(),
-                }),
+                })),
             ),
 
+            test_parse_multiline: (
+                "{\n  \"a\": 1,\n  \"b\" 2\n}",
+                Err(Error::Parse(ParseError {
+                    span: Span { offset: 18, len: 1, line: 3, column: 7 },
+                    message: "Unexpected token `2` in object".to_owned(),
+                })),
+            ),
+            test_parse_tokenize_error: (
+                "[1, x]",
+                Err(Error::Tokenize(TokenizeError {
+                    span: Span { offset: 4, len: 1, line: 1, column: 5 },
+                    message: "Unhandled character".to_owned(),
+                })),
+            ),
+            test_parse_stops_at_first_error: (
+                "[1 2 x]",
+                Err(Error::Parse(ParseError {
+                    span: Span { offset: 3, len: 1, line: 1, column: 4 },
+                    message: "Unexpected token `2` in array".to_owned(),
+                })),
+            ),
             test_parse_stray_token: (
                 "}",
-                Err(ParseError {
-                    offset: 0,
+                Err(Error::Parse(ParseError {
+                    span: Span { offset: 0, len: 1, line: 1, column: 1 },
                     message: "Found unexpected token `}`".to_owned(),
-                }),
+                })),
+            ),
+    }
+
+    #[test]
+    fn test_parse_max_depth() {
+        let nested = |depth: usize| format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+
+        assert!(parse(&nested(128), ParseOptions::default()).is_ok());
+        assert_eq!(
+            parse(&nested(129), ParseOptions::default()),
+            Err(Error::Parse(ParseError {
+                span: Span::new(&nested(129), 128, 1),
+                message: "Nesting is deeper than the maximum of 128".to_owned(),
+            }))
+        );
+
+        let options = ParseOptions {
+            max_depth: 2,
+            ..ParseOptions::default()
+        };
+        assert!(parse("[{\"a\": 1}, []]", options.clone()).is_ok());
+        assert!(parse("[{\"a\": []}]", options).is_err());
+    }
+
+    #[test]
+    fn test_parse_deep_nesting() {
+        let input = "[".repeat(200000);
+        let error = parse(&input, ParseOptions::default()).unwrap_err();
+
+        assert_eq!(error.message(), "Nesting is deeper than the maximum of 128");
+        assert_eq!(error.span().offset, 128);
+    }
+
+    #[test]
+    fn test_parse_lenient_string() {
+        let options = ParseOptions {
+            strict: false,
+            ..ParseOptions::default()
+        };
+        let input = "\"a\\xb\tc\"";
+        assert_eq!(
+            parse(input, options),
+            Ok(Json::String("a\\xb\tc".to_owned()))
+        );
+    }
+
+    macro_rules! duplicate_key_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (input, duplicate_keys, expected) = $value;
+                let options = ParseOptions {
+                    duplicate_keys,
+                    ..ParseOptions::default()
+                };
+                assert_eq!(parse(input, options), expected);
+            }
+        )*
+        }
+    }
+
+    duplicate_key_tests! {
+        test_duplicate_keys_last_wins: (
+                "{\"a\": 1, \"b\": 2, \"a\": 3}",
+                DuplicateKeys::LastWins,
+                Ok(Json::Object(Map::from([
+                    ("a".to_owned(), Json::Number(3.into())),
+                    ("b".to_owned(), Json::Number(2.into())),
+                ]))),
+            ),
+        test_duplicate_keys_first_wins: (
+                "{\"a\": 1, \"b\": 2, \"a\": 3}",
+                DuplicateKeys::FirstWins,
+                Ok(Json::Object(Map::from([
+                    ("a".to_owned(), Json::Number(1.into())),
+                    ("b".to_owned(), Json::Number(2.into())),
+                ]))),
+            ),
+        test_duplicate_keys_collect: (
+                "{\"a\": [1], \"b\": 2, \"a\": 3, \"a\": null}",
+                DuplicateKeys::Collect,
+                Ok(Json::Object(Map::from([
+                    (
+                        "a".to_owned(),
+                        Json::Array(vec![
+                            Json::Array(vec![Json::Number(1.into())]),
+                            Json::Number(3.into()),
+                            Json::Null,
+                        ]),
+                    ),
+                    ("b".to_owned(), Json::Number(2.into())),
+                ]))),
             ),
+        test_duplicate_keys_collect_unique: (
+                "{\"a\": [1]}",
+                DuplicateKeys::Collect,
+                Ok(Json::Object(Map::from([(
+                    "a".to_owned(),
+                    Json::Array(vec![Json::Number(1.into())]),
+                )]))),
+            ),
+        test_duplicate_keys_nested_objects: (
+                "{\"a\": {\"a\": 1}, \"b\": {\"a\": 2}}",
+                DuplicateKeys::Error,
+                Ok(Json::Object(Map::from([
+                    ("a".to_owned(), Json::Object(Map::from([("a".to_owned(), Json::Number(1.into()))]))),
+                    ("b".to_owned(), Json::Object(Map::from([("a".to_owned(), Json::Number(2.into()))]))),
+                ]))),
+            ),
+        test_duplicate_keys_error: (
+                "{\"a\": 1,\n \"b\": 2,\n \"a\": 3}",
+                DuplicateKeys::Error,
+                Err(Error::DuplicateKey(DuplicateKeyError {
+                    span: Span { offset: 19, len: 3, line: 3, column: 2 },
+                    first: Span { offset: 1, len: 3, line: 1, column: 2 },
+                    message: "Duplicate key `a` in object".to_owned(),
+                })),
+            ),
+        test_duplicate_keys_error_escaped: (
+                "{\"a\": 1, \"\\u0061\": 2}",
+                DuplicateKeys::Error,
+                Err(Error::DuplicateKey(DuplicateKeyError {
+                    span: Span { offset: 9, len: 8, line: 1, column: 10 },
+                    first: Span { offset: 1, len: 3, line: 1, column: 2 },
+                    message: "Duplicate key `a` in object".to_owned(),
+                })),
+            ),
+    }
+
+    #[test]
+    fn test_duplicate_key_error_display() {
+        let options = ParseOptions {
+            duplicate_keys: DuplicateKeys::Error,
+            ..ParseOptions::default()
+        };
+        let error = parse("{\"a\": 1, \"a\": 2}", options).unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "1:10: Duplicate key `a` in object (first defined at 1:2)"
+        );
     }
 
     macro_rules! float_parser_tests {
@@ -452,9 +707,7 @@ mod tests {
             #[test]
             fn $name() {
                 let (input, expected) = $value;
-                let tokens = tokenize(input).unwrap();
-                let mut parser = Parser::new(tokens, 0);
-                let parsed = parser.parse_number();
+                let parsed = parse(input, ParseOptions::default());
                 assert_eq!(expected, parsed);
 
             }
@@ -463,11 +716,11 @@ mod tests {
     }
 
     float_parser_tests! {
-        test_parse_number: ("123", Ok(Json::Number(123f64))),
-        test_parse_number_negative: ("-123", Ok(Json::Number(-123f64))),
-        test_parse_number_decimal: ("123.456", Ok(Json::Number(123.456f64))),
-        test_parse_number_negative_decimal: ("-123.456", Ok(Json::Number(-123.456f64))),
-        test_parse_number_exponent: ("69234.2423432E78", Ok(Json::Number(69234.2423432E78f64))),
+        test_parse_number: ("123", Ok(Json::Number(123.into()))),
+        test_parse_number_negative: ("-123", Ok(Json::Number(Number::from(-123)))),
+        test_parse_number_decimal: ("123.456", Ok(Json::Number("123.456".parse().unwrap()))),
+        test_parse_number_negative_decimal: ("-123.456", Ok(Json::Number("-123.456".parse().unwrap()))),
+        test_parse_number_exponent: ("69234.2423432E78", Ok(Json::Number("69234.2423432E78".parse().unwrap()))),
 
     }
 }
@@ -0,0 +1,542 @@
+use std::fmt;
+
+use crate::map::Map;
+use crate::parser::Json;
+use crate::pointer::PointerError;
+
+// A JSON patch that is not a valid patch document, or an operation that can't be applied
+#[derive(Debug, PartialEq)]
+pub struct PatchError {
+    // Index of the operation in the patch
+    pub operation: usize,
+    pub message: String,
+}
+
+impl PatchError {
+    fn new(operation: usize, message: String) -> Self {
+        Self { operation, message }
+    }
+}
+
+impl fmt::Display for PatchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "operation {}: {}", self.operation, self.message)
+    }
+}
+
+impl std::error::Error for PatchError {}
+
+// One operation of a JSON patch (RFC 6902). Paths are JSON pointers.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Operation {
+    Add { path: String, value: Json },
+    Remove { path: String },
+    Replace { path: String, value: Json },
+    Move { from: String, path: String },
+    Copy { from: String, path: String },
+    // Fails the patch when the value at `path` is not equal to `value`
+    Test { path: String, value: Json },
+}
+
+impl Operation {
+    fn name(&self) -> &'static str {
+        match self {
+            Operation::Add { .. } => "add",
+            Operation::Remove { .. } => "remove",
+            Operation::Replace { .. } => "replace",
+            Operation::Move { .. } => "move",
+            Operation::Copy { .. } => "copy",
+            Operation::Test { .. } => "test",
+        }
+    }
+
+    fn apply(&self, json: &mut Json) -> Result<(), String> {
+        match self {
+            Operation::Add { path, value } => {
+                json.pointer_insert(path, value.clone())
+                    .map_err(|error| pointer_error(path, error))?;
+            }
+            Operation::Remove { path } => {
+                json.pointer_remove(path)
+                    .map_err(|error| pointer_error(path, error))?;
+            }
+            Operation::Replace { path, value } => {
+                *json
+                    .pointer_mut(path)
+                    .map_err(|error| pointer_error(path, error))? = value.clone();
+            }
+            Operation::Move { from, path } => {
+                if path.starts_with(from.as_str()) && path[from.len()..].starts_with('/') {
+                    return Err(format!("`{}` can't be moved into itself", from));
+                }
+
+                if from == path {
+                    json.pointer(from)
+                        .map_err(|error| pointer_error(from, error))?;
+                } else {
+                    let value = json
+                        .pointer_remove(from)
+                        .map_err(|error| pointer_error(from, error))?;
+                    json.pointer_insert(path, value)
+                        .map_err(|error| pointer_error(path, error))?;
+                }
+            }
+            Operation::Copy { from, path } => {
+                let value = json
+                    .pointer(from)
+                    .map_err(|error| pointer_error(from, error))?
+                    .clone();
+                json.pointer_insert(path, value)
+                    .map_err(|error| pointer_error(path, error))?;
+            }
+            Operation::Test { path, value } => {
+                let actual = json
+                    .pointer(path)
+                    .map_err(|error| pointer_error(path, error))?;
+
+                if actual != value {
+                    return Err(format!("`{}` is {}, expected {}", path, actual, value));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn to_json(&self) -> Json {
+        let mut map = Map::new();
+        map.insert("op".to_owned(), Json::String(self.name().to_owned()));
+
+        match self {
+            Operation::Add { path, value }
+            | Operation::Replace { path, value }
+            | Operation::Test { path, value } => {
+                map.insert("path".to_owned(), Json::String(path.clone()));
+                map.insert("value".to_owned(), value.clone());
+            }
+            Operation::Remove { path } => {
+                map.insert("path".to_owned(), Json::String(path.clone()));
+            }
+            Operation::Move { from, path } | Operation::Copy { from, path } => {
+                map.insert("from".to_owned(), Json::String(from.clone()));
+                map.insert("path".to_owned(), Json::String(path.clone()));
+            }
+        }
+
+        Json::Object(map)
+    }
+}
+
+fn pointer_error(pointer: &str, error: PointerError) -> String {
+    format!("{}, at `{}`", error.message, pointer)
+}
+
+// A list of operations that is applied as a whole: when one fails, the value is left unchanged
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Patch {
+    pub operations: Vec<Operation>,
+}
+
+impl Patch {
+    // Reads a patch document, which is an array of objects like `{"op": "add", "path": "/a", "value": 1}`
+    pub fn from_json(json: &Json) -> Result<Self, PatchError> {
+        let Json::Array(values) = json else {
+            let message = "A JSON patch must be an array of operations".to_owned();
+            return Err(PatchError::new(0, message));
+        };
+
+        let operations = values
+            .iter()
+            .enumerate()
+            .map(|(index, value)| {
+                parse_operation(value).map_err(|message| PatchError::new(index, message))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { operations })
+    }
+
+    pub fn to_json(&self) -> Json {
+        Json::Array(self.operations.iter().map(Operation::to_json).collect())
+    }
+
+    // Applies all operations in order. They are applied to a copy, which replaces `json` only when all succeed.
+    pub fn apply(&self, json: &mut Json) -> Result<(), PatchError> {
+        let mut patched = json.clone();
+
+        for (index, operation) in self.operations.iter().enumerate() {
+            operation.apply(&mut patched).map_err(|message| {
+                let message = format!("Cannot {}: {}", operation.name(), message);
+                PatchError::new(index, message)
+            })?;
+        }
+
+        *json = patched;
+        Ok(())
+    }
+
+    // Generates a patch that turns `from` into `to`.
+    // Arrays are compared by position, so inserting at the front replaces every element after it.
+    pub fn diff(from: &Json, to: &Json) -> Self {
+        let mut operations = vec![];
+        diff_values(from, to, &mut String::new(), &mut operations);
+
+        Self { operations }
+    }
+}
+
+fn parse_operation(json: &Json) -> Result<Operation, String> {
+    let Json::Object(map) = json else {
+        return Err("An operation must be an object".to_owned());
+    };
+
+    let string = |key: &str| match map.get(key) {
+        Some(Json::String(string)) => Ok(string.clone()),
+        Some(_) => Err(format!("`{}` must be a string", key)),
+        None => Err(format!("Missing `{}`", key)),
+    };
+    let value = || {
+        map.get("value")
+            .cloned()
+            .ok_or_else(|| "Missing `value`".to_owned())
+    };
+
+    let operation = match string("op")?.as_str() {
+        "add" => Operation::Add {
+            path: string("path")?,
+            value: value()?,
+        },
+        "remove" => Operation::Remove {
+            path: string("path")?,
+        },
+        "replace" => Operation::Replace {
+            path: string("path")?,
+            value: value()?,
+        },
+        "move" => Operation::Move {
+            from: string("from")?,
+            path: string("path")?,
+        },
+        "copy" => Operation::Copy {
+            from: string("from")?,
+            path: string("path")?,
+        },
+        "test" => Operation::Test {
+            path: string("path")?,
+            value: value()?,
+        },
+        op => return Err(format!("Unknown operation `{}`", op)),
+    };
+
+    Ok(operation)
+}
+
+// `path` is the pointer to `from` and `to`, it is restored before returning
+fn diff_values(from: &Json, to: &Json, path: &mut String, operations: &mut Vec<Operation>) {
+    if from == to {
+        return;
+    }
+
+    let len = path.len();
+
+    match (from, to) {
+        (Json::Object(from), Json::Object(to)) => {
+            for (key, value) in from.iter() {
+                push_token(path, key);
+
+                match to.get(key) {
+                    Some(other) => diff_values(value, other, path, operations),
+                    None => operations.push(Operation::Remove { path: path.clone() }),
+                }
+
+                path.truncate(len);
+            }
+
+            for (key, value) in to.iter() {
+                if !from.contains_key(key) {
+                    push_token(path, key);
+                    operations.push(Operation::Add {
+                        path: path.clone(),
+                        value: value.clone(),
+                    });
+                    path.truncate(len);
+                }
+            }
+        }
+        (Json::Array(from), Json::Array(to)) => {
+            for (index, (value, other)) in from.iter().zip(to).enumerate() {
+                push_token(path, &index.to_string());
+                diff_values(value, other, path, operations);
+                path.truncate(len);
+            }
+
+            for (index, value) in to.iter().enumerate().skip(from.len()) {
+                push_token(path, &index.to_string());
+                operations.push(Operation::Add {
+                    path: path.clone(),
+                    value: value.clone(),
+                });
+                path.truncate(len);
+            }
+
+            // From the end, so the indices of the elements that are left don't change
+            for index in (to.len()..from.len()).rev() {
+                push_token(path, &index.to_string());
+                operations.push(Operation::Remove { path: path.clone() });
+                path.truncate(len);
+            }
+        }
+        _ => operations.push(Operation::Replace {
+            path: path.clone(),
+            value: to.clone(),
+        }),
+    }
+}
+
+// Appends `/token` to a pointer, escaping `~` and `/`
+fn push_token(path: &mut String, token: &str) {
+    path.push('/');
+    path.push_str(&token.replace('~', "~0").replace('/', "~1"));
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{parse, Json, ParseOptions};
+    use crate::patch::{Operation, Patch, PatchError};
+
+    fn json(input: &str) -> Json {
+        parse(input, ParseOptions::default()).unwrap()
+    }
+
+    fn apply(document: &str, patch: &str) -> Result<Json, PatchError> {
+        let mut document = json(document);
+        Patch::from_json(&json(patch))?.apply(&mut document)?;
+        Ok(document)
+    }
+
+    macro_rules! patch_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (document, patch, expected) = $value;
+                assert_eq!(apply(document, patch), Ok(json(expected)));
+            }
+        )*
+        }
+    }
+
+    // The examples from RFC 6902 appendix A
+    patch_tests! {
+        test_patch_add_member: (
+                r#"{"foo": "bar"}"#,
+                r#"[{"op": "add", "path": "/baz", "value": "qux"}]"#,
+                r#"{"baz": "qux", "foo": "bar"}"#,
+            ),
+        test_patch_add_element: (
+                r#"{"foo": ["bar", "baz"]}"#,
+                r#"[{"op": "add", "path": "/foo/1", "value": "qux"}]"#,
+                r#"{"foo": ["bar", "qux", "baz"]}"#,
+            ),
+        test_patch_remove_member: (
+                r#"{"baz": "qux", "foo": "bar"}"#,
+                r#"[{"op": "remove", "path": "/baz"}]"#,
+                r#"{"foo": "bar"}"#,
+            ),
+        test_patch_remove_element: (
+                r#"{"foo": ["bar", "qux", "baz"]}"#,
+                r#"[{"op": "remove", "path": "/foo/1"}]"#,
+                r#"{"foo": ["bar", "baz"]}"#,
+            ),
+        test_patch_replace: (
+                r#"{"baz": "qux", "foo": "bar"}"#,
+                r#"[{"op": "replace", "path": "/baz", "value": "boo"}]"#,
+                r#"{"baz": "boo", "foo": "bar"}"#,
+            ),
+        test_patch_move_member: (
+                r#"{"foo": {"bar": "baz", "waldo": "fred"}, "qux": {"corge": "grault"}}"#,
+                r#"[{"op": "move", "from": "/foo/waldo", "path": "/qux/thud"}]"#,
+                r#"{"foo": {"bar": "baz"}, "qux": {"corge": "grault", "thud": "fred"}}"#,
+            ),
+        test_patch_move_element: (
+                r#"{"foo": ["all", "grass", "cows", "eat"]}"#,
+                r#"[{"op": "move", "from": "/foo/1", "path": "/foo/3"}]"#,
+                r#"{"foo": ["all", "cows", "eat", "grass"]}"#,
+            ),
+        test_patch_test: (
+                r#"{"baz": "qux", "foo": ["a", 2, "c"]}"#,
+                r#"[{"op": "test", "path": "/baz", "value": "qux"}, {"op": "test", "path": "/foo/1", "value": 2}]"#,
+                r#"{"baz": "qux", "foo": ["a", 2, "c"]}"#,
+            ),
+        test_patch_add_nested: (
+                r#"{"foo": "bar"}"#,
+                r#"[{"op": "add", "path": "/child", "value": {"grandchild": {}}}]"#,
+                r#"{"foo": "bar", "child": {"grandchild": {}}}"#,
+            ),
+        test_patch_ignores_unknown_members: (
+                r#"{"foo": "bar"}"#,
+                r#"[{"op": "add", "path": "/baz", "value": "qux", "xyz": 123}]"#,
+                r#"{"foo": "bar", "baz": "qux"}"#,
+            ),
+        test_patch_escaped_path: (
+                r#"{"/": 9, "~1": 10}"#,
+                r#"[{"op": "test", "path": "/~01", "value": 10}, {"op": "remove", "path": "/~1"}]"#,
+                r#"{"~1": 10}"#,
+            ),
+        test_patch_append: (
+                r#"{"foo": ["bar"]}"#,
+                r#"[{"op": "add", "path": "/foo/-", "value": ["abc", "def"]}]"#,
+                r#"{"foo": ["bar", ["abc", "def"]]}"#,
+            ),
+        test_patch_copy: (
+                r#"{"a": {"b": [1]}}"#,
+                r#"[{"op": "copy", "from": "/a/b", "path": "/c"}, {"op": "add", "path": "/c/-", "value": 2}]"#,
+                r#"{"a": {"b": [1]}, "c": [1, 2]}"#,
+            ),
+        test_patch_replace_root: (
+                r#"{"a": 1}"#,
+                r#"[{"op": "replace", "path": "", "value": [true]}]"#,
+                r#"[true]"#,
+            ),
+        test_patch_test_numbers_by_value: (
+                r#"{"a": 1.0}"#,
+                r#"[{"op": "test", "path": "/a", "value": 1}]"#,
+                r#"{"a": 1}"#,
+            ),
+    }
+
+    macro_rules! patch_error_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (document, patch, operation, message) = $value;
+                let expected = PatchError {
+                    operation,
+                    message: message.to_owned(),
+                };
+
+                assert_eq!(apply(document, patch), Err(expected));
+            }
+        )*
+        }
+    }
+
+    patch_error_tests! {
+        test_patch_error_not_array: ("{}", r#"{"op": "add"}"#, 0, "A JSON patch must be an array of operations"),
+        test_patch_error_unknown_op: ("{}", r#"[{"op": "remove", "path": "/a"}, {"op": "merge"}]"#, 1, "Unknown operation `merge`"),
+        test_patch_error_missing_value: ("{}", r#"[{"op": "add", "path": "/a"}]"#, 0, "Missing `value`"),
+        test_patch_error_path_type: ("{}", r#"[{"op": "remove", "path": 1}]"#, 0, "`path` must be a string"),
+        test_patch_error_missing_parent: (
+                r#"{"foo": "bar"}"#,
+                r#"[{"op": "add", "path": "/baz/bat", "value": "qux"}]"#,
+                0,
+                "Cannot add: Key `baz` not found in object, at `/baz/bat`",
+            ),
+        test_patch_error_test_failed: (
+                r#"{"baz": "qux"}"#,
+                r#"[{"op": "test", "path": "/baz", "value": "bar"}]"#,
+                0,
+                "Cannot test: `/baz` is \"qux\", expected \"bar\"",
+            ),
+        test_patch_error_out_of_bounds: (
+                r#"{"foo": [1]}"#,
+                r#"[{"op": "replace", "path": "/foo/1", "value": 2}]"#,
+                0,
+                "Cannot replace: Index `1` is out of bounds for array of length 1, at `/foo/1`",
+            ),
+        test_patch_error_move_into_itself: (
+                r#"{"a": {"b": {}}}"#,
+                r#"[{"op": "move", "from": "/a", "path": "/a/b/c"}]"#,
+                0,
+                "Cannot move: `/a` can't be moved into itself",
+            ),
+    }
+
+    #[test]
+    fn test_patch_is_atomic() {
+        let mut document = json(r#"{"a": [1, 2], "b": "x"}"#);
+        let patch = Patch::from_json(&json(
+            r#"[
+                {"op": "remove", "path": "/a/0"},
+                {"op": "add", "path": "/c", "value": true},
+                {"op": "test", "path": "/b", "value": "y"}
+            ]"#,
+        ))
+        .unwrap();
+
+        assert_eq!(patch.apply(&mut document).unwrap_err().operation, 2);
+        assert_eq!(document, json(r#"{"a": [1, 2], "b": "x"}"#));
+    }
+
+    #[test]
+    fn test_patch_to_json() {
+        let patch = json(
+            r#"[
+                {"op": "add", "path": "/a", "value": [1]},
+                {"op": "remove", "path": "/b"},
+                {"op": "move", "from": "/c", "path": "/d"},
+                {"op": "test", "path": "", "value": null}
+            ]"#,
+        );
+
+        assert_eq!(Patch::from_json(&patch).unwrap().to_json(), patch);
+    }
+
+    #[test]
+    fn test_diff() {
+        let from = json(r#"{"a": 1, "b": {"c": [1, 2, 3]}, "d/e": "x"}"#);
+        let to = json(r#"{"a": 1, "b": {"c": [1, 5]}, "f": null}"#);
+
+        let mut operations = Patch::diff(&from, &to).operations;
+        // Without `preserve_order` the keys of an object are visited in any order
+        operations.sort_by_key(|operation| format!("{:?}", operation));
+
+        assert_eq!(
+            operations,
+            [
+                Operation::Add {
+                    path: "/f".to_owned(),
+                    value: Json::Null,
+                },
+                Operation::Remove {
+                    path: "/b/c/2".to_owned(),
+                },
+                Operation::Remove {
+                    path: "/d~1e".to_owned(),
+                },
+                Operation::Replace {
+                    path: "/b/c/1".to_owned(),
+                    value: Json::Number(5.into()),
+                },
+            ]
+        );
+    }
+
+    macro_rules! diff_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (from, to) = $value;
+                let (from, to) = (json(from), json(to));
+
+                let mut patched = from.clone();
+                Patch::diff(&from, &to).apply(&mut patched).unwrap();
+                assert_eq!(patched, to);
+            }
+        )*
+        }
+    }
+
+    diff_tests! {
+        test_diff_equal: (r#"{"a": [1, {"b": 2}]}"#, r#"{"a": [1, {"b": 2.0}]}"#),
+        test_diff_root: ("[1]", "\"x\""),
+        test_diff_longer_array: ("[1]", "[1, 2, [3]]"),
+        test_diff_shorter_array: (r#"[1, 2, 3, 4]"#, r#"[0]"#),
+        test_diff_nested: (
+                r#"{"a": {"b": [{"c": 1}, 2]}, "~": 1}"#,
+                r#"{"a": {"b": [{"c": 2, "d": []}]}, "x": {"~": 1}}"#,
+            ),
+    }
+}
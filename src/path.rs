@@ -0,0 +1,1246 @@
+use std::fmt::{self, Write};
+
+use regex::Regex;
+
+use crate::iregexp;
+use crate::number::Number;
+use crate::parser::Json;
+use crate::span::Span;
+
+// Integers in paths must fit in a double exactly, like in I-JSON
+const MAX_INTEGER: i64 = (1 << 53) - 1;
+
+// A JSONPath (RFC 9535) that can't be parsed. The span points into the path.
+#[derive(Debug, PartialEq)]
+pub struct PathError {
+    pub span: Span,
+    pub message: String,
+}
+
+impl PathError {
+    fn new(path: &str, offset: usize, len: usize, message: String) -> Self {
+        Self {
+            span: Span::new(path, offset, len),
+            message,
+        }
+    }
+}
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.span, self.message)
+    }
+}
+
+impl std::error::Error for PathError {}
+
+// A value selected by a query, with its normalized path like `$['store']['book'][0]`
+#[derive(Debug, PartialEq)]
+pub struct Node<'a> {
+    pub path: String,
+    pub value: &'a Json,
+}
+
+// A parsed JSONPath, which can be used to query any number of values
+#[derive(Debug)]
+pub struct JsonPath {
+    query: Query,
+}
+
+impl JsonPath {
+    pub fn parse(path: &str) -> Result<Self, PathError> {
+        let mut parser = PathParser { path, offset: 0 };
+
+        if !parser.eat("$") {
+            return parser.unexpected("`$`");
+        }
+
+        let query = parser.parse_query(false)?;
+        if parser.offset < path.len() {
+            return parser.unexpected("a segment");
+        }
+
+        Ok(Self { query })
+    }
+
+    // Returns the selected values in document order, a value can be selected more than once
+    pub fn query<'a>(&self, json: &'a Json) -> Vec<Node<'a>> {
+        let context = Context {
+            root: json,
+            paths: true,
+        };
+
+        self.query.select(json, &context)
+    }
+}
+
+impl Json {
+    // Parses `path` and runs it, use `JsonPath` to run the same path more than once
+    pub fn query(&self, path: &str) -> Result<Vec<Node<'_>>, PathError> {
+        Ok(JsonPath::parse(path)?.query(self))
+    }
+}
+
+#[derive(Debug)]
+struct Query {
+    // Starts at the current value `@` instead of the root `$`
+    relative: bool,
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug)]
+enum Segment {
+    Child(Vec<Selector>),
+    Descendant(Vec<Selector>),
+}
+
+#[derive(Debug)]
+enum Selector {
+    Name(String),
+    Wildcard,
+    Index(i64),
+    Slice {
+        start: Option<i64>,
+        end: Option<i64>,
+        step: Option<i64>,
+    },
+    Filter(Filter),
+}
+
+#[derive(Debug)]
+enum Filter {
+    Or(Vec<Filter>),
+    And(Vec<Filter>),
+    Not(Box<Filter>),
+    Exists(Query),
+    Compare(Operand, Comparison, Operand),
+    // `match()` if `whole`, otherwise `search()`
+    Match {
+        text: Operand,
+        pattern: Pattern,
+        whole: bool,
+    },
+}
+
+// The I-Regexp of `match()` and `search()`, compiled up front when it's a literal
+#[derive(Debug)]
+enum Pattern {
+    // `None` if the literal is not a valid I-Regexp, which never matches
+    Literal(Option<Regex>),
+    Operand(Operand),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+// Something that produces at most one value in a comparison
+#[derive(Debug)]
+enum Operand {
+    Literal(Json),
+    // Always a singular query, which selects at most one value
+    Query(Query),
+    Length(Box<Operand>),
+    Count(Query),
+    Value(Query),
+}
+
+impl Query {
+    // Only names and indices, so at most one value is selected
+    fn is_singular(&self) -> bool {
+        self.segments.iter().all(|segment| match segment {
+            Segment::Child(selectors) => {
+                matches!(selectors[..], [Selector::Name(_) | Selector::Index(_)])
+            }
+            Segment::Descendant(_) => false,
+        })
+    }
+
+    fn select<'a>(&self, current: &'a Json, context: &Context<'a>) -> Vec<Node<'a>> {
+        let start = if self.relative { current } else { context.root };
+        let mut nodes = vec![Node {
+            path: context.path("$"),
+            value: start,
+        }];
+
+        for segment in &self.segments {
+            let mut selected = vec![];
+
+            for node in &nodes {
+                match segment {
+                    Segment::Child(selectors) => {
+                        for selector in selectors {
+                            selector.select(node, context, &mut selected);
+                        }
+                    }
+                    Segment::Descendant(selectors) => {
+                        select_descendants(selectors, node, context, &mut selected)
+                    }
+                }
+            }
+
+            nodes = selected;
+        }
+
+        nodes
+    }
+}
+
+// Applies the selectors to `node` and then to everything nested in it, depth first
+fn select_descendants<'a>(
+    selectors: &[Selector],
+    node: &Node<'a>,
+    context: &Context<'a>,
+    selected: &mut Vec<Node<'a>>,
+) {
+    for selector in selectors {
+        selector.select(node, context, selected);
+    }
+
+    match node.value {
+        Json::Array(array) => {
+            for (index, value) in array.iter().enumerate() {
+                let child = context.element(node, index, value);
+                select_descendants(selectors, &child, context, selected);
+            }
+        }
+        Json::Object(map) => {
+            for (key, value) in map.iter() {
+                let child = context.member(node, key, value);
+                select_descendants(selectors, &child, context, selected);
+            }
+        }
+        _ => {}
+    }
+}
+
+impl Selector {
+    fn select<'a>(&self, node: &Node<'a>, context: &Context<'a>, selected: &mut Vec<Node<'a>>) {
+        match (self, node.value) {
+            (Selector::Name(name), Json::Object(map)) => {
+                if let Some(value) = map.get(name) {
+                    selected.push(context.member(node, name, value));
+                }
+            }
+            (Selector::Wildcard, Json::Array(array)) => {
+                for (index, value) in array.iter().enumerate() {
+                    selected.push(context.element(node, index, value));
+                }
+            }
+            (Selector::Wildcard, Json::Object(map)) => {
+                for (key, value) in map.iter() {
+                    selected.push(context.member(node, key, value));
+                }
+            }
+            (&Selector::Index(index), Json::Array(array)) => {
+                let len = array.len() as i64;
+                let index = if index < 0 { len + index } else { index };
+
+                if (0..len).contains(&index) {
+                    let index = index as usize;
+                    selected.push(context.element(node, index, &array[index]));
+                }
+            }
+            (&Selector::Slice { start, end, step }, Json::Array(array)) => {
+                for index in slice_indices(array.len() as i64, start, end, step) {
+                    let index = index as usize;
+                    selected.push(context.element(node, index, &array[index]));
+                }
+            }
+            (Selector::Filter(filter), Json::Array(array)) => {
+                for (index, value) in array.iter().enumerate() {
+                    if filter.test(value, context) {
+                        selected.push(context.element(node, index, value));
+                    }
+                }
+            }
+            (Selector::Filter(filter), Json::Object(map)) => {
+                for (key, value) in map.iter() {
+                    if filter.test(value, context) {
+                        selected.push(context.member(node, key, value));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+// Indices selected by `[start:end:step]` in an array of length `len`, following RFC 9535 section 2.3.4.2
+fn slice_indices(
+    len: i64,
+    start: Option<i64>,
+    end: Option<i64>,
+    step: Option<i64>,
+) -> impl Iterator<Item = i64> {
+    let step = step.unwrap_or(1);
+    let normalize = |index: i64| if index < 0 { len + index } else { index };
+
+    let (mut index, bound) = if step >= 0 {
+        let lower = normalize(start.unwrap_or(0)).clamp(0, len);
+        let upper = normalize(end.unwrap_or(len)).clamp(0, len);
+        (lower, upper)
+    } else {
+        let upper = normalize(start.unwrap_or(len - 1)).clamp(-1, len - 1);
+        let lower = normalize(end.unwrap_or(-len - 1)).clamp(-1, len - 1);
+        (upper, lower)
+    };
+
+    std::iter::from_fn(move || {
+        let more = match step {
+            0 => false,
+            step if step > 0 => index < bound,
+            _ => index > bound,
+        };
+
+        if !more {
+            return None;
+        }
+
+        let current = index;
+        index += step;
+        Some(current)
+    })
+}
+
+impl Filter {
+    fn test<'a>(&self, current: &'a Json, context: &Context<'a>) -> bool {
+        match self {
+            Filter::Or(filters) => filters.iter().any(|filter| filter.test(current, context)),
+            Filter::And(filters) => filters.iter().all(|filter| filter.test(current, context)),
+            Filter::Not(filter) => !filter.test(current, context),
+            Filter::Exists(query) => !query.select(current, &context.without_paths()).is_empty(),
+            Filter::Compare(left, comparison, right) => {
+                let context = context.without_paths();
+                let left = left.evaluate(current, &context);
+                let right = right.evaluate(current, &context);
+
+                compare(
+                    left.as_ref().map(Value::get),
+                    *comparison,
+                    right.as_ref().map(Value::get),
+                )
+            }
+            Filter::Match {
+                text,
+                pattern,
+                whole,
+            } => {
+                let context = context.without_paths();
+                let text = text.evaluate(current, &context);
+                let Some(Json::String(text)) = text.as_ref().map(Value::get) else {
+                    return false;
+                };
+
+                match pattern {
+                    Pattern::Literal(regex) => {
+                        regex.as_ref().is_some_and(|regex| regex.is_match(text))
+                    }
+                    Pattern::Operand(operand) => {
+                        match operand.evaluate(current, &context).as_ref().map(Value::get) {
+                            Some(Json::String(pattern)) => iregexp::compile(pattern, *whole)
+                                .is_some_and(|regex| regex.is_match(text)),
+                            _ => false,
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+// The result of an operand, which is computed by functions like `length`
+enum Value<'a> {
+    Borrowed(&'a Json),
+    Owned(Json),
+}
+
+impl Value<'_> {
+    fn get(&self) -> &Json {
+        match self {
+            Value::Borrowed(json) => json,
+            Value::Owned(json) => json,
+        }
+    }
+}
+
+impl Operand {
+    // Returns `None` when nothing is selected or a function has no result
+    fn evaluate<'a>(&'a self, current: &'a Json, context: &Context<'a>) -> Option<Value<'a>> {
+        match self {
+            Operand::Literal(json) => Some(Value::Borrowed(json)),
+            Operand::Query(query) => query
+                .select(current, context)
+                .pop()
+                .map(|node| Value::Borrowed(node.value)),
+            Operand::Length(operand) => {
+                let len = match operand.evaluate(current, context)?.get() {
+                    Json::String(string) => string.chars().count(),
+                    Json::Array(array) => array.len(),
+                    Json::Object(map) => map.len(),
+                    _ => return None,
+                };
+
+                Some(Value::Owned(Json::Number(len.into())))
+            }
+            Operand::Count(query) => {
+                let count = query.select(current, context).len();
+                Some(Value::Owned(Json::Number(count.into())))
+            }
+            Operand::Value(query) => match query.select(current, context).as_slice() {
+                [node] => Some(Value::Borrowed(node.value)),
+                _ => None,
+            },
+        }
+    }
+}
+
+fn compare(left: Option<&Json>, comparison: Comparison, right: Option<&Json>) -> bool {
+    match comparison {
+        Comparison::Equal => left == right,
+        Comparison::NotEqual => left != right,
+        Comparison::Less => less(left, right),
+        Comparison::LessOrEqual => less(left, right) || left == right,
+        Comparison::Greater => less(right, left),
+        Comparison::GreaterOrEqual => less(right, left) || left == right,
+    }
+}
+
+// Only numbers and strings are ordered, strings by their code points
+fn less(left: Option<&Json>, right: Option<&Json>) -> bool {
+    match (left, right) {
+        (Some(Json::Number(left)), Some(Json::Number(right))) => {
+            left.as_decimal() < right.as_decimal()
+        }
+        (Some(Json::String(left)), Some(Json::String(right))) => left < right,
+        _ => false,
+    }
+}
+
+struct Context<'a> {
+    root: &'a Json,
+    // Paths are only built for the nodes that are returned, not for queries inside filters
+    paths: bool,
+}
+
+impl<'a> Context<'a> {
+    fn without_paths(&self) -> Self {
+        Self {
+            root: self.root,
+            paths: false,
+        }
+    }
+
+    fn path(&self, path: &str) -> String {
+        if self.paths {
+            path.to_owned()
+        } else {
+            String::new()
+        }
+    }
+
+    fn member(&self, parent: &Node<'a>, key: &str, value: &'a Json) -> Node<'a> {
+        let mut path = self.path(&parent.path);
+        if self.paths {
+            write_name(&mut path, key);
+        }
+
+        Node { path, value }
+    }
+
+    fn element(&self, parent: &Node<'a>, index: usize, value: &'a Json) -> Node<'a> {
+        let mut path = self.path(&parent.path);
+        if self.paths {
+            write!(path, "[{}]", index).unwrap();
+        }
+
+        Node { path, value }
+    }
+}
+
+// Writes `['key']`, escaped like a normalized path in RFC 9535 section 2.7
+fn write_name(path: &mut String, key: &str) {
+    path.push_str("['");
+
+    for c in key.chars() {
+        match c {
+            '\'' => path.push_str("\\'"),
+            '\\' => path.push_str("\\\\"),
+            '\u{8}' => path.push_str("\\b"),
+            '\u{c}' => path.push_str("\\f"),
+            '\n' => path.push_str("\\n"),
+            '\r' => path.push_str("\\r"),
+            '\t' => path.push_str("\\t"),
+            '\u{0}'..='\u{1f}' => write!(path, "\\u{:04x}", c as u32).unwrap(),
+            c => path.push(c),
+        }
+    }
+
+    path.push_str("']");
+}
+
+struct PathParser<'p> {
+    path: &'p str,
+    offset: usize,
+}
+
+impl<'p> PathParser<'p> {
+    fn rest(&self) -> &'p str {
+        &self.path[self.offset..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn eat(&mut self, text: &str) -> bool {
+        let found = self.rest().starts_with(text);
+        if found {
+            self.offset += text.len();
+        }
+        found
+    }
+
+    fn skip_blank(&mut self) {
+        let rest = self.rest();
+        self.offset += rest.len() - rest.trim_start_matches([' ', '\t', '\n', '\r']).len();
+    }
+
+    fn error<T>(&self, offset: usize, message: String) -> Result<T, PathError> {
+        Err(PathError::new(
+            self.path,
+            offset,
+            self.offset.max(offset) - offset,
+            message,
+        ))
+    }
+
+    fn unexpected<T>(&self, expected: &str) -> Result<T, PathError> {
+        let (len, found) = match self.peek() {
+            Some(c) => (c.len_utf8(), format!("`{}`", c)),
+            None => (0, "end of path".to_owned()),
+        };
+        let message = format!("Unexpected {}, expected {}", found, expected);
+
+        Err(PathError::new(self.path, self.offset, len, message))
+    }
+
+    fn expect(&mut self, text: &str) -> Result<(), PathError> {
+        if !self.eat(text) {
+            return self.unexpected(&format!("`{}`", text));
+        }
+        Ok(())
+    }
+
+    // Parses the segments after `$` or `@`
+    fn parse_query(&mut self, relative: bool) -> Result<Query, PathError> {
+        let mut segments = vec![];
+
+        loop {
+            let before = self.offset;
+            self.skip_blank();
+
+            let segment = if self.eat("..") {
+                Segment::Descendant(self.parse_shorthand_or_bracketed()?)
+            } else if self.eat(".") {
+                Segment::Child(self.parse_shorthand()?)
+            } else if self.eat("[") {
+                Segment::Child(self.parse_bracketed()?)
+            } else {
+                self.offset = before;
+                return Ok(Query { relative, segments });
+            };
+
+            segments.push(segment);
+        }
+    }
+
+    fn parse_shorthand_or_bracketed(&mut self) -> Result<Vec<Selector>, PathError> {
+        if self.eat("[") {
+            self.parse_bracketed()
+        } else {
+            self.parse_shorthand()
+        }
+    }
+
+    // A `*` or member name after a dot, like `.name`
+    fn parse_shorthand(&mut self) -> Result<Vec<Selector>, PathError> {
+        if self.eat("*") {
+            return Ok(vec![Selector::Wildcard]);
+        }
+
+        let mut chars = self.rest().char_indices();
+        let is_name_first = |c: char| c.is_ascii_alphabetic() || c == '_' || !c.is_ascii();
+
+        let len = match chars.next() {
+            Some((_, c)) if is_name_first(c) => chars
+                .find(|&(_, c)| !is_name_first(c) && !c.is_ascii_digit())
+                .map_or(self.rest().len(), |(index, _)| index),
+            _ => return self.unexpected("a member name or `*`"),
+        };
+
+        let name = self.rest()[..len].to_owned();
+        self.offset += len;
+
+        Ok(vec![Selector::Name(name)])
+    }
+
+    // Selectors separated by commas, after the opening `[`
+    fn parse_bracketed(&mut self) -> Result<Vec<Selector>, PathError> {
+        let mut selectors = vec![];
+
+        loop {
+            self.skip_blank();
+            selectors.push(self.parse_selector()?);
+            self.skip_blank();
+
+            if self.eat("]") {
+                return Ok(selectors);
+            }
+            if !self.eat(",") {
+                return self.unexpected("`,` or `]`");
+            }
+        }
+    }
+
+    fn parse_selector(&mut self) -> Result<Selector, PathError> {
+        match self.peek() {
+            Some('\'' | '"') => Ok(Selector::Name(self.parse_string()?)),
+            Some('*') => {
+                self.offset += 1;
+                Ok(Selector::Wildcard)
+            }
+            Some('?') => {
+                self.offset += 1;
+                self.skip_blank();
+                Ok(Selector::Filter(self.parse_or()?))
+            }
+            Some('-' | '0'..='9' | ':') => self.parse_index_or_slice(),
+            _ => self.unexpected("a selector"),
+        }
+    }
+
+    fn parse_index_or_slice(&mut self) -> Result<Selector, PathError> {
+        let start = self.parse_integer()?;
+        self.skip_blank();
+
+        if !self.eat(":") {
+            return match start {
+                Some(index) => Ok(Selector::Index(index)),
+                None => self.unexpected("an index"),
+            };
+        }
+
+        self.skip_blank();
+        let end = self.parse_integer()?;
+        self.skip_blank();
+
+        let mut step = None;
+        if self.eat(":") {
+            self.skip_blank();
+            step = self.parse_integer()?;
+        }
+
+        Ok(Selector::Slice { start, end, step })
+    }
+
+    // Returns `None` when there is no integer, so slices can leave out their bounds
+    fn parse_integer(&mut self) -> Result<Option<i64>, PathError> {
+        let start = self.offset;
+        let sign = usize::from(self.rest().starts_with('-'));
+        let digits = self.rest()[sign..]
+            .bytes()
+            .take_while(u8::is_ascii_digit)
+            .count();
+
+        if digits == 0 {
+            if sign == 1 {
+                self.offset += 1;
+                return self.unexpected("a digit");
+            }
+            return Ok(None);
+        }
+
+        let text = &self.rest()[..sign + digits];
+        self.offset += text.len();
+
+        if (digits > 1 && text[sign..].starts_with('0')) || text == "-0" {
+            return self.error(start, format!("`{}` is not a valid integer", text));
+        }
+
+        match text.parse::<i64>() {
+            Ok(integer) if (-MAX_INTEGER..=MAX_INTEGER).contains(&integer) => Ok(Some(integer)),
+            _ => self.error(start, format!("Integer `{}` is out of range", text)),
+        }
+    }
+
+    // A string in single or double quotes, with JSON escape sequences
+    fn parse_string(&mut self) -> Result<String, PathError> {
+        let start = self.offset;
+        let quote = self.peek();
+        self.offset += 1;
+
+        let mut string = String::new();
+
+        loop {
+            let Some(c) = self.peek() else {
+                return self.error(start, "Unterminated string".to_owned());
+            };
+
+            let escape = self.offset;
+            self.offset += c.len_utf8();
+
+            match c {
+                '\\' => string.push(self.parse_escape(quote, escape)?),
+                '\u{0}'..='\u{1f}' => {
+                    let message = format!("Control character {:?} must be escaped", c);
+                    return self.error(escape, message);
+                }
+                c if Some(c) == quote => return Ok(string),
+                c => string.push(c),
+            }
+        }
+    }
+
+    fn parse_escape(&mut self, quote: Option<char>, escape: usize) -> Result<char, PathError> {
+        let Some(c) = self.peek() else {
+            return self.error(escape, "Unterminated escape sequence".to_owned());
+        };
+        self.offset += c.len_utf8();
+
+        match c {
+            'b' => Ok('\u{8}'),
+            'f' => Ok('\u{c}'),
+            'n' => Ok('\n'),
+            'r' => Ok('\r'),
+            't' => Ok('\t'),
+            '/' | '\\' => Ok(c),
+            'u' => self.parse_unicode_escape(escape),
+            c if Some(c) == quote => Ok(c),
+            c => self.error(escape, format!("Invalid escape sequence `\\{}`", c)),
+        }
+    }
+
+    fn parse_unicode_escape(&mut self, escape: usize) -> Result<char, PathError> {
+        let high = self.parse_hex(escape)?;
+
+        let code_point = match high {
+            0xd800..=0xdbff => {
+                let low = if self.eat("\\u") {
+                    self.parse_hex(escape)?
+                } else {
+                    0
+                };
+
+                if !(0xdc00..=0xdfff).contains(&low) {
+                    return self.error(escape, "Unpaired UTF-16 surrogate".to_owned());
+                }
+
+                0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)
+            }
+            0xdc00..=0xdfff => {
+                return self.error(escape, "Unpaired UTF-16 surrogate".to_owned());
+            }
+            _ => high,
+        };
+
+        Ok(char::from_u32(code_point).unwrap())
+    }
+
+    fn parse_hex(&mut self, escape: usize) -> Result<u32, PathError> {
+        let hex = self
+            .rest()
+            .get(..4)
+            .filter(|hex| hex.bytes().all(|byte| byte.is_ascii_hexdigit()));
+
+        let Some(hex) = hex else {
+            let message = "Expected 4 hexadecimal digits after `\\u`".to_owned();
+            return self.error(escape, message);
+        };
+
+        self.offset += 4;
+        Ok(u32::from_str_radix(hex, 16).unwrap())
+    }
+
+    fn parse_or(&mut self) -> Result<Filter, PathError> {
+        let mut filters = vec![self.parse_and()?];
+
+        loop {
+            let before = self.offset;
+            self.skip_blank();
+
+            if !self.eat("||") {
+                self.offset = before;
+                break;
+            }
+
+            self.skip_blank();
+            filters.push(self.parse_and()?);
+        }
+
+        Ok(match filters.len() {
+            1 => filters.pop().unwrap(),
+            _ => Filter::Or(filters),
+        })
+    }
+
+    fn parse_and(&mut self) -> Result<Filter, PathError> {
+        let mut filters = vec![self.parse_basic()?];
+
+        loop {
+            let before = self.offset;
+            self.skip_blank();
+
+            if !self.eat("&&") {
+                self.offset = before;
+                break;
+            }
+
+            self.skip_blank();
+            filters.push(self.parse_basic()?);
+        }
+
+        Ok(match filters.len() {
+            1 => filters.pop().unwrap(),
+            _ => Filter::And(filters),
+        })
+    }
+
+    // A parenthesized expression, a comparison or an existence test, optionally negated
+    fn parse_basic(&mut self) -> Result<Filter, PathError> {
+        if self.eat("!") {
+            self.skip_blank();
+
+            if self.eat("(") {
+                return Ok(Filter::Not(Box::new(self.parse_parenthesized()?)));
+            }
+            if let Some(filter) = self.parse_match()? {
+                return Ok(Filter::Not(Box::new(filter)));
+            }
+
+            let start = self.offset;
+            return match self.parse_operand()? {
+                Operand::Query(query) => Ok(Filter::Not(Box::new(Filter::Exists(query)))),
+                _ => self.error(start, "Only queries and `(` can follow `!`".to_owned()),
+            };
+        }
+
+        if self.eat("(") {
+            return self.parse_parenthesized();
+        }
+        if let Some(filter) = self.parse_match()? {
+            return Ok(filter);
+        }
+
+        let start = self.offset;
+        let left = self.parse_operand()?;
+        let end = self.offset;
+        self.skip_blank();
+
+        let Some(comparison) = self.parse_comparison() else {
+            self.offset = end;
+
+            return match left {
+                Operand::Query(query) => Ok(Filter::Exists(query)),
+                _ => self.unexpected("a comparison operator"),
+            };
+        };
+
+        self.check_comparable(&left, start, end)?;
+        self.skip_blank();
+
+        let start = self.offset;
+        let right = self.parse_operand()?;
+        self.check_comparable(&right, start, self.offset)?;
+
+        Ok(Filter::Compare(left, comparison, right))
+    }
+
+    // `match(..)` and `search(..)` are tests on their own, unlike the other functions
+    fn parse_match(&mut self) -> Result<Option<Filter>, PathError> {
+        let (name, whole) = if self.eat("match(") {
+            ("match", true)
+        } else if self.eat("search(") {
+            ("search", false)
+        } else {
+            return Ok(None);
+        };
+
+        self.skip_blank();
+        let start = self.offset;
+        let text = self.parse_operand()?;
+        self.check_comparable(&text, start, self.offset)?;
+
+        self.skip_blank();
+        self.expect(",")?;
+        self.skip_blank();
+
+        let start = self.offset;
+        let pattern = match self.parse_operand()? {
+            Operand::Literal(Json::String(pattern)) => {
+                Pattern::Literal(iregexp::compile(&pattern, whole))
+            }
+            Operand::Literal(_) => Pattern::Literal(None),
+            operand => {
+                self.check_comparable(&operand, start, self.offset)?;
+                Pattern::Operand(operand)
+            }
+        };
+
+        self.skip_blank();
+        self.expect(")")?;
+
+        let end = self.offset;
+        self.skip_blank();
+        let start = self.offset;
+        if self.parse_comparison().is_some() {
+            let message = format!("Function `{}` is a test and can't be compared", name);
+            return self.error(start, message);
+        }
+        self.offset = end;
+
+        Ok(Some(Filter::Match {
+            text,
+            pattern,
+            whole,
+        }))
+    }
+
+    fn parse_parenthesized(&mut self) -> Result<Filter, PathError> {
+        self.skip_blank();
+        let filter = self.parse_or()?;
+        self.skip_blank();
+        self.expect(")")?;
+
+        Ok(filter)
+    }
+
+    fn parse_comparison(&mut self) -> Option<Comparison> {
+        let comparisons = [
+            ("==", Comparison::Equal),
+            ("!=", Comparison::NotEqual),
+            ("<=", Comparison::LessOrEqual),
+            (">=", Comparison::GreaterOrEqual),
+            ("<", Comparison::Less),
+            (">", Comparison::Greater),
+        ];
+
+        comparisons
+            .into_iter()
+            .find(|(operator, _)| self.eat(operator))
+            .map(|(_, comparison)| comparison)
+    }
+
+    fn check_comparable(
+        &self,
+        operand: &Operand,
+        start: usize,
+        end: usize,
+    ) -> Result<(), PathError> {
+        match operand {
+            Operand::Query(query) if !query.is_singular() => {
+                let message =
+                    "Only queries with just names and indices can be compared, use `value()` for others"
+                        .to_owned();
+                Err(PathError::new(self.path, start, end - start, message))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn parse_operand(&mut self) -> Result<Operand, PathError> {
+        let start = self.offset;
+
+        match self.peek() {
+            Some('@') => {
+                self.offset += 1;
+                Ok(Operand::Query(self.parse_query(true)?))
+            }
+            Some('$') => {
+                self.offset += 1;
+                Ok(Operand::Query(self.parse_query(false)?))
+            }
+            Some('\'' | '"') => Ok(Operand::Literal(Json::String(self.parse_string()?))),
+            Some('-' | '0'..='9') => Ok(Operand::Literal(Json::Number(self.parse_number()?))),
+            Some('a'..='z') => {
+                let len = self
+                    .rest()
+                    .bytes()
+                    .take_while(|&byte| matches!(byte, b'a'..=b'z' | b'0'..=b'9' | b'_'))
+                    .count();
+                let name = &self.rest()[..len];
+                self.offset += len;
+
+                if self.rest().starts_with('(') {
+                    return self.parse_function(name, start);
+                }
+
+                match name {
+                    "true" => Ok(Operand::Literal(Json::Boolean(true))),
+                    "false" => Ok(Operand::Literal(Json::Boolean(false))),
+                    "null" => Ok(Operand::Literal(Json::Null)),
+                    _ => self.error(start, format!("Unknown literal `{}`", name)),
+                }
+            }
+            _ => self.unexpected("a query, literal or function"),
+        }
+    }
+
+    fn parse_number(&mut self) -> Result<Number, PathError> {
+        let start = self.offset;
+        let len = self
+            .rest()
+            .find(|c: char| !c.is_ascii_digit() && !matches!(c, '-' | '+' | '.' | 'e' | 'E'))
+            .unwrap_or(self.rest().len());
+
+        let text = &self.rest()[..len];
+        self.offset += len;
+
+        match text.parse() {
+            Ok(number) => Ok(number),
+            Err(_) => self.error(start, format!("`{}` is not a valid number", text)),
+        }
+    }
+
+    // `name` is already consumed, the arguments start at the `(`
+    fn parse_function(&mut self, name: &str, start: usize) -> Result<Operand, PathError> {
+        if matches!(name, "match" | "search") {
+            let message = format!("Function `{}` is a test and can't be used as a value", name);
+            return self.error(start, message);
+        }
+
+        if !matches!(name, "length" | "count" | "value") {
+            return self.error(start, format!("Unknown function `{}`", name));
+        }
+
+        self.offset += 1;
+        self.skip_blank();
+
+        let argument_start = self.offset;
+        let argument = self.parse_operand()?;
+
+        let operand = match (name, argument) {
+            ("length", argument) => {
+                self.check_comparable(&argument, argument_start, self.offset)?;
+                Operand::Length(Box::new(argument))
+            }
+            ("count", Operand::Query(query)) => Operand::Count(query),
+            ("value", Operand::Query(query)) => Operand::Value(query),
+            _ => {
+                let message = format!("Function `{}` takes a query", name);
+                return self.error(argument_start, message);
+            }
+        };
+
+        self.skip_blank();
+        self.expect(")")?;
+
+        Ok(operand)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{parse, Json, ParseOptions};
+    use crate::path::{JsonPath, PathError};
+    use crate::span::Span;
+
+    fn json(input: &str) -> Json {
+        parse(input, ParseOptions::default()).unwrap()
+    }
+
+    // The example from RFC 9535 section 1.5
+    fn bookstore() -> Json {
+        json(
+            r#"{ "store": {
+                "book": [
+                    { "category": "reference", "author": "Nigel Rees", "title": "Sayings of the Century", "price": 8.95 },
+                    { "category": "fiction", "author": "Evelyn Waugh", "title": "Sword of Honour", "price": 12.99 },
+                    { "category": "fiction", "author": "Herman Melville", "title": "Moby Dick", "isbn": "0-553-21311-3", "price": 8.99 },
+                    { "category": "fiction", "author": "J. R. R. Tolkien", "title": "The Lord of the Rings", "isbn": "0-395-19395-8", "price": 22.99 }
+                ],
+                "bicycle": { "color": "red", "price": 399 }
+            } }"#,
+        )
+    }
+
+    fn assert_paths(document: &Json, path: &str, expected: &[&str]) {
+        let nodes = document.query(path).unwrap();
+        let mut paths: Vec<_> = nodes.into_iter().map(|node| node.path).collect();
+        let mut expected = expected.to_vec();
+
+        // Without `preserve_order` the members of an object are visited in any order
+        if !cfg!(feature = "preserve_order") {
+            paths.sort();
+            expected.sort();
+        }
+
+        assert_eq!(paths, expected);
+    }
+
+    macro_rules! path_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (path, expected): (&str, &[&str]) = $value;
+                assert_paths(&bookstore(), path, expected);
+            }
+        )*
+        }
+    }
+
+    path_tests! {
+        test_path_root: ("$", &["$"]),
+        test_path_authors: (
+                "$.store.book[*].author",
+                &[
+                    "$['store']['book'][0]['author']",
+                    "$['store']['book'][1]['author']",
+                    "$['store']['book'][2]['author']",
+                    "$['store']['book'][3]['author']",
+                ],
+            ),
+        test_path_store_wildcard: ("$.store.*", &["$['store']['book']", "$['store']['bicycle']"]),
+        test_path_descendant_prices: (
+                "$.store..price",
+                &[
+                    "$['store']['book'][0]['price']",
+                    "$['store']['book'][1]['price']",
+                    "$['store']['book'][2]['price']",
+                    "$['store']['book'][3]['price']",
+                    "$['store']['bicycle']['price']",
+                ],
+            ),
+        test_path_bracketed_names: ("$['store'][\"bicycle\"]['color']", &["$['store']['bicycle']['color']"]),
+        test_path_index: ("$..book[2]", &["$['store']['book'][2]"]),
+        test_path_negative_index: ("$..book[-1]", &["$['store']['book'][3]"]),
+        test_path_index_out_of_bounds: ("$..book[4]", &[]),
+        test_path_multiple_selectors: ("$..book[0, 1]", &["$['store']['book'][0]", "$['store']['book'][1]"]),
+        test_path_slice: ("$..book[:2]", &["$['store']['book'][0]", "$['store']['book'][1]"]),
+        test_path_slice_step: ("$.store.book[1::2]", &["$['store']['book'][1]", "$['store']['book'][3]"]),
+        test_path_slice_reversed: (
+                "$.store.book[::-1]",
+                &["$['store']['book'][3]", "$['store']['book'][2]", "$['store']['book'][1]", "$['store']['book'][0]"],
+            ),
+        test_path_slice_zero_step: ("$.store.book[0:4:0]", &[]),
+        test_path_filter_exists: ("$..book[?@.isbn]", &["$['store']['book'][2]", "$['store']['book'][3]"]),
+        test_path_filter_not_exists: ("$..book[?!@.isbn].title", &["$['store']['book'][0]['title']", "$['store']['book'][1]['title']"]),
+        test_path_filter_less: (
+                "$.store.book[?@.price < 10].title",
+                &["$['store']['book'][0]['title']", "$['store']['book'][2]['title']"],
+            ),
+        test_path_filter_root: ("$..book[?@.price < $.store.bicycle.price].price", &["$['store']['book'][0]['price']", "$['store']['book'][1]['price']", "$['store']['book'][2]['price']", "$['store']['book'][3]['price']"]),
+        test_path_filter_string: ("$..book[?@.author == 'Evelyn Waugh'].price", &["$['store']['book'][1]['price']"]),
+        test_path_filter_logical: (
+                "$..book[?(@.category == \"fiction\" && @.price < 10) || @.price > 20].title",
+                &["$['store']['book'][2]['title']", "$['store']['book'][3]['title']"],
+            ),
+        test_path_filter_object: ("$.store[?@.color]", &["$['store']['bicycle']"]),
+        test_path_filter_length: ("$..book[?length(@.title) > 20].title", &["$['store']['book'][0]['title']", "$['store']['book'][3]['title']"]),
+        test_path_filter_count: ("$.store.book[?count(@.*) == 5].isbn", &["$['store']['book'][2]['isbn']", "$['store']['book'][3]['isbn']"]),
+        test_path_filter_value: ("$.store[?value(@..color) == 'red']", &["$['store']['bicycle']"]),
+        test_path_filter_match: ("$..book[?match(@.category, 'fic.*')].price", &["$['store']['book'][1]['price']", "$['store']['book'][2]['price']", "$['store']['book'][3]['price']"]),
+        test_path_filter_match_whole: ("$..book[?match(@.category, 'fic')]", &[]),
+        test_path_filter_search: ("$..book[?search(@.author, 'R\\\\.')].title", &["$['store']['book'][3]['title']"]),
+        test_path_filter_not_match: ("$..book[?!match(@.isbn, '0-3.*')].title", &["$['store']['book'][0]['title']", "$['store']['book'][1]['title']", "$['store']['book'][2]['title']"]),
+        test_path_filter_match_query: ("$.store[?match(@.color, $.store.bicycle.color)]", &["$['store']['bicycle']"]),
+        test_path_filter_match_invalid_pattern: ("$..book[?search(@.isbn, '\\\\d')]", &[]),
+        test_path_filter_match_not_string: ("$..book[?match(@.price, '8.*')]", &[]),
+        test_path_filter_missing_equals_missing: ("$.store.book[?@.isbn == @.missing].title", &["$['store']['book'][0]['title']", "$['store']['book'][1]['title']"]),
+        test_path_descendant_wildcard: ("$.store.bicycle..*", &["$['store']['bicycle']['color']", "$['store']['bicycle']['price']"]),
+    }
+
+    #[test]
+    fn test_path_values() {
+        let document = bookstore();
+        let nodes = document.query("$.store.book[?@.price < 10].title").unwrap();
+        let titles: Vec<_> = nodes.iter().map(|node| node.value).collect();
+
+        assert_eq!(
+            titles,
+            [
+                &Json::String("Sayings of the Century".to_owned()),
+                &Json::String("Moby Dick".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_path_reused() {
+        let path = JsonPath::parse("$[?@ >= 2]").unwrap();
+
+        assert_eq!(path.query(&json("[1, 2, 3.5]")).len(), 2);
+        assert_eq!(path.query(&json("{\"a\": 2.0, \"b\": \"2\"}")).len(), 1);
+        assert!(path.query(&json("null")).is_empty());
+    }
+
+    #[test]
+    fn test_path_compares_exactly() {
+        let document = json("[9007199254740993, 9007199254740992, 0.30000000000000000001]");
+
+        assert_paths(&document, "$[?@ > 9007199254740992]", &["$[0]"]);
+        assert_paths(&document, "$[?@ == 0.3]", &[]);
+    }
+
+    #[test]
+    fn test_path_escaped_names() {
+        let document = json(r#"{"it's": {"back\\slash": 1, "tab\t": 2, "\u0001": 3}}"#);
+
+        assert_paths(
+            &document,
+            "$['it\\'s'].*",
+            &[
+                "$['it\\'s']['back\\\\slash']",
+                "$['it\\'s']['tab\\t']",
+                "$['it\\'s']['\\u0001']",
+            ],
+        );
+        assert_paths(
+            &document,
+            "$[\"it's\"][\"\\u0001\"]",
+            &["$['it\\'s']['\\u0001']"],
+        );
+    }
+
+    macro_rules! path_error_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (path, offset, len, message) = $value;
+                let expected = PathError {
+                    span: Span::new(path, offset, len),
+                    message: message.to_owned(),
+                };
+
+                assert_eq!(JsonPath::parse(path).unwrap_err(), expected);
+            }
+        )*
+        }
+    }
+
+    path_error_tests! {
+        test_path_error_no_root: ("store", 0, 1, "Unexpected `s`, expected `$`"),
+        test_path_error_trailing: ("$.a ", 3, 1, "Unexpected ` `, expected a segment"),
+        test_path_error_member_name: ("$.1", 2, 1, "Unexpected `1`, expected a member name or `*`"),
+        test_path_error_unclosed_bracket: ("$[0", 3, 0, "Unexpected end of path, expected `,` or `]`"),
+        test_path_error_leading_zero: ("$[01]", 2, 2, "`01` is not a valid integer"),
+        test_path_error_integer_range: ("$[9007199254740992]", 2, 16, "Integer `9007199254740992` is out of range"),
+        test_path_error_unterminated_string: ("$['a", 2, 2, "Unterminated string"),
+        test_path_error_escape: ("$['\\a']", 3, 2, "Invalid escape sequence `\\a`"),
+        test_path_error_non_singular: ("$[?@.* == 1]", 3, 3, "Only queries with just names and indices can be compared, use `value()` for others"),
+        test_path_error_literal_test: ("$[?1]", 4, 1, "Unexpected `]`, expected a comparison operator"),
+        test_path_error_unknown_function: ("$[?foo(@) == 1]", 3, 3, "Unknown function `foo`"),
+        test_path_error_match_value: ("$[?length(match(@, 'a.*')) == 1]", 10, 5, "Function `match` is a test and can't be used as a value"),
+        test_path_error_match_compared: ("$[?search(@, 'a') == true]", 18, 2, "Function `search` is a test and can't be compared"),
+        test_path_error_match_non_singular: ("$[?match(@.*, 'a')]", 9, 3, "Only queries with just names and indices can be compared, use `value()` for others"),
+        test_path_error_count_argument: ("$[?count(1) == 1]", 9, 1, "Function `count` takes a query"),
+    }
+}
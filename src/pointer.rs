@@ -0,0 +1,388 @@
+use std::fmt;
+use std::mem;
+
+use crate::parser::Json;
+use crate::span::Span;
+
+// A JSON pointer (RFC 6901) that is invalid, or that does not fit the value it is used on.
+// The span points into the pointer.
+#[derive(Debug, PartialEq)]
+pub struct PointerError {
+    pub span: Span,
+    pub message: String,
+}
+
+impl PointerError {
+    fn new(pointer: &str, offset: usize, len: usize, message: String) -> Self {
+        Self {
+            span: Span::new(pointer, offset, len),
+            message,
+        }
+    }
+}
+
+impl fmt::Display for PointerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.span, self.message)
+    }
+}
+
+impl std::error::Error for PointerError {}
+
+// One part of a pointer between slashes, with its escape sequences decoded
+struct ReferenceToken {
+    key: String,
+    // Byte offset and length of the part in the pointer
+    offset: usize,
+    len: usize,
+}
+
+impl ReferenceToken {
+    fn error(&self, pointer: &str, message: String) -> PointerError {
+        PointerError::new(pointer, self.offset, self.len, message)
+    }
+}
+
+fn parse_pointer(pointer: &str) -> Result<Vec<ReferenceToken>, PointerError> {
+    if pointer.is_empty() {
+        return Ok(vec![]);
+    }
+
+    if !pointer.starts_with('/') {
+        let message = "JSON pointer must be empty or start with `/`".to_owned();
+        return Err(PointerError::new(pointer, 0, 1, message));
+    }
+
+    let mut tokens = vec![];
+    let mut offset = 1;
+
+    for part in pointer[1..].split('/') {
+        let mut key = String::with_capacity(part.len());
+        let mut chars = part.char_indices();
+
+        while let Some((index, c)) = chars.next() {
+            if c != '~' {
+                key.push(c);
+                continue;
+            }
+
+            match chars.next() {
+                Some((_, '0')) => key.push('~'),
+                Some((_, '1')) => key.push('/'),
+                next => {
+                    let len = 1 + next.map_or(0, |(_, c)| c.len_utf8());
+                    let message = format!(
+                        "Invalid escape `{}`, use `~0` for `~` and `~1` for `/`",
+                        &part[index..index + len]
+                    );
+
+                    return Err(PointerError::new(pointer, offset + index, len, message));
+                }
+            }
+        }
+
+        tokens.push(ReferenceToken {
+            key,
+            offset,
+            len: part.len(),
+        });
+        offset += part.len() + 1;
+    }
+
+    Ok(tokens)
+}
+
+pub(crate) fn type_name(json: &Json) -> &'static str {
+    match json {
+        Json::Null => "null",
+        Json::Boolean(_) => "boolean",
+        Json::Number(_) => "number",
+        Json::String(_) => "string",
+        Json::Array(_) => "array",
+        Json::Object(_) => "object",
+    }
+}
+
+fn mismatch_error(pointer: &str, token: &ReferenceToken, json: &Json) -> PointerError {
+    let message = format!("Cannot look up `{}` in {}", token.key, type_name(json));
+    token.error(pointer, message)
+}
+
+fn missing_key_error(pointer: &str, token: &ReferenceToken) -> PointerError {
+    token.error(pointer, format!("Key `{}` not found in object", token.key))
+}
+
+// Converts a token to an array index, `-` is the position after the last element.
+// Indices up to `len` are valid when `allow_end` is set, otherwise up to `len - 1`.
+fn index(
+    pointer: &str,
+    token: &ReferenceToken,
+    len: usize,
+    allow_end: bool,
+) -> Result<usize, PointerError> {
+    let key = token.key.as_str();
+
+    let index = match key {
+        "-" => Some(len),
+        // Leading zeros and signs are not allowed
+        _ if key.starts_with('0') && key != "0" => None,
+        _ if !key.is_empty() && key.bytes().all(|b| b.is_ascii_digit()) => key.parse().ok(),
+        _ => None,
+    }
+    .ok_or_else(|| token.error(pointer, format!("`{}` is not a valid array index", key)))?;
+
+    if index > len || (index == len && !allow_end) {
+        let message = format!(
+            "Index `{}` is out of bounds for array of length {}",
+            key, len
+        );
+        return Err(token.error(pointer, message));
+    }
+
+    Ok(index)
+}
+
+fn child<'j>(
+    json: &'j Json,
+    pointer: &str,
+    token: &ReferenceToken,
+) -> Result<&'j Json, PointerError> {
+    match json {
+        Json::Object(map) => map
+            .get(&token.key)
+            .ok_or_else(|| missing_key_error(pointer, token)),
+        Json::Array(values) => Ok(&values[index(pointer, token, values.len(), false)?]),
+        _ => Err(mismatch_error(pointer, token, json)),
+    }
+}
+
+fn child_mut<'j>(
+    json: &'j mut Json,
+    pointer: &str,
+    token: &ReferenceToken,
+) -> Result<&'j mut Json, PointerError> {
+    match json {
+        Json::Object(map) => map
+            .get_mut(&token.key)
+            .ok_or_else(|| missing_key_error(pointer, token)),
+        Json::Array(values) => {
+            let index = index(pointer, token, values.len(), false)?;
+            Ok(&mut values[index])
+        }
+        _ => Err(mismatch_error(pointer, token, json)),
+    }
+}
+
+impl Json {
+    // Looks up a value by JSON pointer, like `/foo/0/bar`. The empty pointer refers to the value itself.
+    pub fn pointer(&self, pointer: &str) -> Result<&Json, PointerError> {
+        parse_pointer(pointer)?
+            .iter()
+            .try_fold(self, |json, token| child(json, pointer, token))
+    }
+
+    pub fn pointer_mut(&mut self, pointer: &str) -> Result<&mut Json, PointerError> {
+        parse_pointer(pointer)?
+            .iter()
+            .try_fold(self, |json, token| child_mut(json, pointer, token))
+    }
+
+    // Sets a key of an object, or inserts into an array and shifts the elements after it.
+    // The parent of `pointer` must exist, `-` appends to an array. Returns the value that was replaced, if any.
+    pub fn pointer_insert(
+        &mut self,
+        pointer: &str,
+        value: Json,
+    ) -> Result<Option<Json>, PointerError> {
+        let tokens = parse_pointer(pointer)?;
+
+        let Some((last, parents)) = tokens.split_last() else {
+            return Ok(Some(mem::replace(self, value)));
+        };
+
+        let parent = parents
+            .iter()
+            .try_fold(self, |json, token| child_mut(json, pointer, token))?;
+
+        match parent {
+            Json::Object(map) => Ok(map.insert(last.key.clone(), value)),
+            Json::Array(values) => {
+                let index = index(pointer, last, values.len(), true)?;
+                values.insert(index, value);
+                Ok(None)
+            }
+            _ => Err(mismatch_error(pointer, last, parent)),
+        }
+    }
+
+    // Removes a key of an object, or an element of an array and shifts the elements after it
+    pub fn pointer_remove(&mut self, pointer: &str) -> Result<Json, PointerError> {
+        let tokens = parse_pointer(pointer)?;
+
+        let Some((last, parents)) = tokens.split_last() else {
+            let message = "Cannot remove the root value".to_owned();
+            return Err(PointerError::new(pointer, 0, 0, message));
+        };
+
+        let parent = parents
+            .iter()
+            .try_fold(self, |json, token| child_mut(json, pointer, token))?;
+
+        match parent {
+            Json::Object(map) => map
+                .remove(&last.key)
+                .ok_or_else(|| missing_key_error(pointer, last)),
+            Json::Array(values) => {
+                let index = index(pointer, last, values.len(), false)?;
+                Ok(values.remove(index))
+            }
+            _ => Err(mismatch_error(pointer, last, parent)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{parse, Json, ParseOptions};
+    use crate::pointer::PointerError;
+    use crate::span::Span;
+
+    fn json(input: &str) -> Json {
+        parse(input, ParseOptions::default()).unwrap()
+    }
+
+    // The example document of RFC 6901
+    fn document() -> Json {
+        json(
+            r#"{"foo": ["bar", "baz"], "": 0, "a/b": 1, "c%d": 2, "e^f": 3, "g|h": 4,
+                "i\\j": 5, "k\"l": 6, " ": 7, "m~n": 8, "nested": {"0": [true]}}"#,
+        )
+    }
+
+    macro_rules! pointer_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (pointer, expected): (&str, Result<&str, &str>) = $value;
+                let document = document();
+
+                let found = document.pointer(pointer);
+                let expected = expected.map(json);
+                assert_eq!(
+                    found.map_err(|error| error.to_string()),
+                    expected.as_ref().map_err(|message| message.to_string())
+                );
+            }
+        )*
+        }
+    }
+
+    pointer_tests! {
+        test_pointer_key: ("/foo", Ok("[\"bar\", \"baz\"]")),
+        test_pointer_index: ("/foo/0", Ok("\"bar\"")),
+        test_pointer_empty_key: ("/", Ok("0")),
+        test_pointer_escaped_slash: ("/a~1b", Ok("1")),
+        test_pointer_percent: ("/c%d", Ok("2")),
+        test_pointer_backslash: ("/i\\j", Ok("5")),
+        test_pointer_quote: ("/k\"l", Ok("6")),
+        test_pointer_space: ("/ ", Ok("7")),
+        test_pointer_escaped_tilde: ("/m~0n", Ok("8")),
+        test_pointer_numeric_key: ("/nested/0/0", Ok("true")),
+        test_pointer_missing_slash: ("foo", Err("1:1: JSON pointer must be empty or start with `/`")),
+        test_pointer_invalid_escape: ("/m~2n", Err("1:3: Invalid escape `~2`, use `~0` for `~` and `~1` for `/`")),
+        test_pointer_trailing_tilde: ("/m~", Err("1:3: Invalid escape `~`, use `~0` for `~` and `~1` for `/`")),
+        test_pointer_missing_key: ("/nested/x", Err("1:9: Key `x` not found in object")),
+        test_pointer_out_of_bounds: ("/foo/2", Err("1:6: Index `2` is out of bounds for array of length 2")),
+        test_pointer_dash: ("/foo/-", Err("1:6: Index `-` is out of bounds for array of length 2")),
+        test_pointer_leading_zero: ("/foo/01", Err("1:6: `01` is not a valid array index")),
+        test_pointer_negative: ("/foo/-1", Err("1:6: `-1` is not a valid array index")),
+        test_pointer_type_mismatch: ("/foo/0/x", Err("1:8: Cannot look up `x` in string")),
+    }
+
+    #[test]
+    fn test_pointer_mut() {
+        let mut value = document();
+        assert_eq!(value.pointer(""), Ok(&document()));
+
+        *value.pointer_mut("/foo/1").unwrap() = Json::Null;
+        assert_eq!(value.pointer("/foo"), Ok(&json("[\"bar\", null]")));
+    }
+
+    #[test]
+    fn test_pointer_insert() {
+        let mut document = json(r#"{"a": [1, 2], "b": {"c": 3}}"#);
+
+        assert_eq!(document.pointer_insert("/a/0", json("0")), Ok(None));
+        assert_eq!(document.pointer_insert("/a/-", json("3")), Ok(None));
+        assert_eq!(
+            document.pointer_insert("/b/c", json("4")),
+            Ok(Some(json("3")))
+        );
+        assert_eq!(document.pointer_insert("/b/d~1e", json("5")), Ok(None));
+        assert_eq!(
+            document,
+            json(r#"{"a": [0, 1, 2, 3], "b": {"c": 4, "d/e": 5}}"#)
+        );
+
+        assert_eq!(
+            document.pointer_insert("/x/y", Json::Null),
+            Err(PointerError {
+                span: Span {
+                    offset: 1,
+                    len: 1,
+                    line: 1,
+                    column: 2
+                },
+                message: "Key `x` not found in object".to_owned(),
+            })
+        );
+        assert_eq!(
+            document
+                .pointer_insert("/a/5", Json::Null)
+                .unwrap_err()
+                .message,
+            "Index `5` is out of bounds for array of length 4"
+        );
+        assert_eq!(
+            document
+                .pointer_insert("/a/0/x", Json::Null)
+                .unwrap_err()
+                .message,
+            "Cannot look up `x` in number"
+        );
+    }
+
+    #[test]
+    fn test_pointer_insert_root() {
+        let mut document = json("[1]");
+
+        assert_eq!(
+            document.pointer_insert("", Json::Null),
+            Ok(Some(json("[1]")))
+        );
+        assert_eq!(document, Json::Null);
+    }
+
+    #[test]
+    fn test_pointer_remove() {
+        let mut document = json(r#"{"a": [1, 2, 3], "b": {"c": 3}}"#);
+
+        assert_eq!(document.pointer_remove("/a/1"), Ok(json("2")));
+        assert_eq!(document.pointer_remove("/b/c"), Ok(json("3")));
+        assert_eq!(document, json(r#"{"a": [1, 3], "b": {}}"#));
+
+        assert_eq!(
+            document.pointer_remove("/b/c").unwrap_err().message,
+            "Key `c` not found in object"
+        );
+        assert_eq!(
+            document.pointer_remove("/a/-").unwrap_err().message,
+            "Index `-` is out of bounds for array of length 2"
+        );
+        assert_eq!(
+            document.pointer_remove("").unwrap_err().message,
+            "Cannot remove the root value"
+        );
+    }
+}
@@ -0,0 +1,404 @@
+use std::fmt::{self, Write};
+use std::io;
+
+use crate::map::Map;
+use crate::parser::Json;
+use crate::serializer::{write_json, write_string};
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Indent {
+    Spaces(usize),
+    // Counts as 4 columns towards `max_width`
+    Tabs,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Newline {
+    Lf,
+    CrLf,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct PrettyOptions {
+    pub indent: Indent,
+    pub sort_keys: bool,
+    // Arrays and objects that fit within this many columns are kept on one line,
+    // use 0 to always put every item on its own line
+    pub max_width: usize,
+    pub newline: Newline,
+}
+
+impl Default for PrettyOptions {
+    fn default() -> Self {
+        Self {
+            indent: Indent::Spaces(2),
+            sort_keys: false,
+            max_width: 80,
+            newline: Newline::Lf,
+        }
+    }
+}
+
+fn entries(object: &Map, sort_keys: bool) -> Vec<(&String, &Json)> {
+    let mut entries: Vec<(&String, &Json)> = object.iter().collect();
+
+    if sort_keys {
+        entries.sort_by(|a, b| a.0.cmp(b.0));
+    }
+
+    entries
+}
+
+// Renders a value on a single line, giving up as soon as it gets wider than `limit`
+struct FlatPrinter {
+    output: String,
+    width: usize,
+    limit: usize,
+    sort_keys: bool,
+}
+
+impl FlatPrinter {
+    fn push(&mut self, s: &str) -> bool {
+        self.width += s.chars().count();
+        self.output.push_str(s);
+        self.width <= self.limit
+    }
+
+    fn print(&mut self, json: &Json) -> bool {
+        match json {
+            Json::Array(array) => {
+                if !self.push("[") {
+                    return false;
+                }
+                for (index, item) in array.iter().enumerate() {
+                    if index > 0 && !self.push(", ") {
+                        return false;
+                    }
+                    if !self.print(item) {
+                        return false;
+                    }
+                }
+                self.push("]")
+            }
+            Json::Object(object) => {
+                if !self.push("{") {
+                    return false;
+                }
+                for (index, (key, value)) in entries(object, self.sort_keys).into_iter().enumerate()
+                {
+                    if index > 0 && !self.push(", ") {
+                        return false;
+                    }
+                    let mut key_string = String::new();
+                    // Writing to a `String` never fails
+                    write_string(&mut key_string, key).unwrap();
+                    if !self.push(&key_string) || !self.push(": ") || !self.print(value) {
+                        return false;
+                    }
+                }
+                self.push("}")
+            }
+            scalar => self.push(&scalar.to_string()),
+        }
+    }
+}
+
+struct PrettyPrinter<'a, W> {
+    output: &'a mut W,
+    options: &'a PrettyOptions,
+}
+
+impl<'a, W: Write> PrettyPrinter<'a, W> {
+    fn indent_width(&self, depth: usize) -> usize {
+        match self.options.indent {
+            Indent::Spaces(width) => width * depth,
+            Indent::Tabs => 4 * depth,
+        }
+    }
+
+    fn newline(&mut self, depth: usize) -> fmt::Result {
+        match self.options.newline {
+            Newline::Lf => self.output.write_char('\n')?,
+            Newline::CrLf => self.output.write_str("\r\n")?,
+        }
+
+        for _ in 0..depth {
+            match self.options.indent {
+                Indent::Spaces(width) => write!(self.output, "{:width$}", "", width = width)?,
+                Indent::Tabs => self.output.write_char('\t')?,
+            }
+        }
+
+        Ok(())
+    }
+
+    // Tries to print `json` on one line, `column` is where it starts and `trailing`
+    // is the width of what follows it on the same line
+    fn print_flat(
+        &mut self,
+        json: &Json,
+        column: usize,
+        trailing: usize,
+    ) -> Result<bool, fmt::Error> {
+        let mut flat = FlatPrinter {
+            output: String::new(),
+            width: 0,
+            limit: self.options.max_width.saturating_sub(column + trailing),
+            sort_keys: self.options.sort_keys,
+        };
+
+        if !flat.print(json) {
+            return Ok(false);
+        }
+
+        self.output.write_str(&flat.output)?;
+        Ok(true)
+    }
+
+    fn print(&mut self, json: &Json, depth: usize, column: usize, trailing: usize) -> fmt::Result {
+        match json {
+            Json::Array(array) if !array.is_empty() => {
+                if self.print_flat(json, column, trailing)? {
+                    return Ok(());
+                }
+
+                self.output.write_char('[')?;
+                for (index, item) in array.iter().enumerate() {
+                    let is_last = index + 1 == array.len();
+
+                    self.newline(depth + 1)?;
+                    self.print(
+                        item,
+                        depth + 1,
+                        self.indent_width(depth + 1),
+                        !is_last as usize,
+                    )?;
+                    if !is_last {
+                        self.output.write_char(',')?;
+                    }
+                }
+                self.newline(depth)?;
+                self.output.write_char(']')
+            }
+            Json::Object(object) if !object.is_empty() => {
+                if self.print_flat(json, column, trailing)? {
+                    return Ok(());
+                }
+
+                let entries = entries(object, self.options.sort_keys);
+
+                self.output.write_char('{')?;
+                for (index, (key, value)) in entries.iter().enumerate() {
+                    let is_last = index + 1 == entries.len();
+
+                    let mut key_string = String::new();
+                    write_string(&mut key_string, key)?;
+
+                    self.newline(depth + 1)?;
+                    self.output.write_str(&key_string)?;
+                    self.output.write_str(": ")?;
+
+                    let column = self.indent_width(depth + 1) + key_string.chars().count() + 2;
+                    self.print(value, depth + 1, column, !is_last as usize)?;
+                    if !is_last {
+                        self.output.write_char(',')?;
+                    }
+                }
+                self.newline(depth)?;
+                self.output.write_char('}')
+            }
+            _ => write_json(self.output, json),
+        }
+    }
+}
+
+// Lets `write!` stream pretty output into an `io::Write`, like `Display` does for compact output
+struct Pretty<'a> {
+    json: &'a Json,
+    options: &'a PrettyOptions,
+}
+
+impl<'a> fmt::Display for Pretty<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut printer = PrettyPrinter {
+            output: f,
+            options: self.options,
+        };
+
+        printer.print(self.json, 0, 0, 0)
+    }
+}
+
+pub fn to_string_pretty(json: &Json, options: PrettyOptions) -> String {
+    let mut output = String::new();
+    let mut printer = PrettyPrinter {
+        output: &mut output,
+        options: &options,
+    };
+
+    // Writing to a `String` never fails
+    printer.print(json, 0, 0, 0).unwrap();
+    output
+}
+
+pub fn to_writer_pretty<W: io::Write>(
+    mut writer: W,
+    json: &Json,
+    options: PrettyOptions,
+) -> io::Result<()> {
+    let pretty = Pretty {
+        json,
+        options: &options,
+    };
+
+    write!(writer, "{}", pretty)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use crate::map::Map;
+    use crate::parse;
+    use crate::parser::Json;
+    use crate::pretty::{to_string_pretty, to_writer_pretty, Indent, Newline, PrettyOptions};
+
+    macro_rules! pretty_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (input, options, expected): (&str, PrettyOptions, &[&str]) = $value;
+                let json = parse(input).unwrap();
+                assert_eq!(to_string_pretty(&json, options), expected.join("\n"));
+            }
+        )*
+        }
+    }
+
+    fn options(max_width: usize) -> PrettyOptions {
+        PrettyOptions {
+            max_width,
+            sort_keys: true,
+            ..PrettyOptions::default()
+        }
+    }
+
+    pretty_tests! {
+        test_pretty_scalar: ("3.0", PrettyOptions::default(), &["3.0"]),
+        test_pretty_empty_array: ("[]", options(0), &["[]"]),
+        test_pretty_empty_object: ("{}", options(0), &["{}"]),
+        test_pretty_short_array: ("[1,2,3]", PrettyOptions::default(), &["[1, 2, 3]"]),
+        test_pretty_short_object: ("{\"a\":1,\"b\":[true]}", options(80), &["{\"a\": 1, \"b\": [true]}"]),
+        test_pretty_expanded: (
+                "{\"b\":[1,2],\"a\":{\"c\":null,\"d\":{}}}",
+                options(0),
+                &[
+                    "{",
+                    "  \"a\": {",
+                    "    \"c\": null,",
+                    "    \"d\": {}",
+                    "  },",
+                    "  \"b\": [",
+                    "    1,",
+                    "    2",
+                    "  ]",
+                    "}",
+                ],
+            ),
+        test_pretty_nested_fits: (
+                "{\"numbers\":[1,2,3],\"words\":[\"aaaaaaaaaa\",\"bbbbbbbbbb\",\"cccccccccc\"]}",
+                options(40),
+                &[
+                    "{",
+                    "  \"numbers\": [1, 2, 3],",
+                    "  \"words\": [",
+                    "    \"aaaaaaaaaa\",",
+                    "    \"bbbbbbbbbb\",",
+                    "    \"cccccccccc\"",
+                    "  ]",
+                    "}",
+                ],
+            ),
+        test_pretty_width_includes_comma: (
+                "[[1,2],[3,4]]",
+                options(12),
+                &[
+                    "[",
+                    "  [1, 2],",
+                    "  [3, 4]",
+                    "]",
+                ],
+            ),
+        test_pretty_exact_width: ("[[1,2],[3,4]]", options(16), &["[[1, 2], [3, 4]]"]),
+        test_pretty_tabs: (
+                "[{\"a\":1}]",
+                PrettyOptions { indent: Indent::Tabs, max_width: 0, ..PrettyOptions::default() },
+                &["[", "\t{", "\t\t\"a\": 1", "\t}", "]"],
+            ),
+        test_pretty_four_spaces: (
+                "[1]",
+                PrettyOptions { indent: Indent::Spaces(4), max_width: 0, ..PrettyOptions::default() },
+                &["[", "    1", "]"],
+            ),
+    }
+
+    #[test]
+    fn test_pretty_crlf() {
+        let json = Json::Array(vec![Json::Null]);
+        let options = PrettyOptions {
+            newline: Newline::CrLf,
+            max_width: 0,
+            ..PrettyOptions::default()
+        };
+
+        assert_eq!(to_string_pretty(&json, options), "[\r\n  null\r\n]");
+    }
+
+    #[test]
+    fn test_pretty_round_trip() {
+        let input = "{\"a\": [1, {\"b\": \"c\\nd\"}], \"e\": -1.5e-9}";
+        let json = parse(input).unwrap();
+        let pretty = to_string_pretty(&json, options(0));
+
+        assert_eq!(parse(&pretty), Ok(json));
+    }
+
+    #[test]
+    fn test_to_writer_pretty() {
+        let json = Json::Object(Map::from([("a".to_owned(), Json::Boolean(true))]));
+        let mut output = vec![];
+
+        to_writer_pretty(&mut output, &json, options(0)).unwrap();
+        assert_eq!(output, b"{\n  \"a\": true\n}");
+    }
+
+    // Accepts as many bytes as its second field, then fails
+    struct FullWriter(Vec<u8>, usize);
+
+    impl io::Write for FullWriter {
+        fn write(&mut self, buffer: &[u8]) -> io::Result<usize> {
+            if self.0.len() == self.1 {
+                return Err(io::Error::new(io::ErrorKind::WriteZero, "full"));
+            }
+
+            let len = buffer.len().min(self.1 - self.0.len());
+            self.0.extend_from_slice(&buffer[..len]);
+            Ok(len)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_to_writer_pretty_error() {
+        let json = parse("[1, [2, 3], 4]").unwrap();
+        let mut writer = FullWriter(vec![], 5);
+
+        let error = to_writer_pretty(&mut writer, &json, options(0)).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::WriteZero);
+        assert_eq!(writer.0, b"[\n  1");
+    }
+}
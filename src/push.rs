@@ -0,0 +1,334 @@
+use crate::error::Error;
+use crate::parser::{parse, Json, ParseOptions};
+use crate::span::Span;
+use crate::tokenizer::TokenizeError;
+
+// Where the scanner is in the buffered input
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum State {
+    // Skipping whitespace before the next value
+    Between,
+    // Inside a number or literal, which ends at the first delimiter
+    Scalar,
+    // Inside a string, array or object, which ends when `depth` drops to zero outside a string
+    Nested {
+        depth: usize,
+        in_string: bool,
+        // The previous byte was a backslash that escapes this one
+        escaped: bool,
+    },
+}
+
+// Parses a stream of JSON values that arrives in chunks of bytes.
+// Chunks can split tokens and even characters anywhere, values are parsed once all their bytes arrived.
+// Values are separated by optional whitespace, like `{"a": 1} [2] 3`.
+// The bytes of a top level value are buffered until it is complete, and are then parsed in one go:
+// the scanner only finds where a value ends, so a single large value takes its full size in memory.
+pub struct PushParser {
+    options: ParseOptions,
+    // Bytes that were fed, the ones before `consumed` were parsed already
+    buffer: Vec<u8>,
+    consumed: usize,
+    // Number of bytes in `buffer` that the scanner already looked at
+    scanned: usize,
+    // Offset of the value that is being scanned in `buffer`
+    value_start: usize,
+    state: State,
+    // Position of the first byte after `consumed` in the stream
+    position: Span,
+    // No more chunks will be fed
+    finished: bool,
+}
+
+impl PushParser {
+    pub fn new(options: ParseOptions) -> Self {
+        Self {
+            options,
+            buffer: vec![],
+            consumed: 0,
+            scanned: 0,
+            value_start: 0,
+            state: State::Between,
+            position: Span::new("", 0, 0),
+            finished: false,
+        }
+    }
+
+    pub fn feed(&mut self, chunk: &[u8]) {
+        assert!(!self.finished, "PushParser was fed after finish()");
+
+        // Parsed bytes are only dropped here, so parsing many values from one chunk stays linear
+        self.buffer.drain(..self.consumed);
+        self.scanned -= self.consumed;
+        self.value_start = self.value_start.saturating_sub(self.consumed);
+        self.consumed = 0;
+
+        self.buffer.extend_from_slice(chunk);
+    }
+
+    // Signals the end of the stream, which completes a number at the very end of it.
+    // After this `next_value` reports values that are cut off as errors.
+    pub fn finish(&mut self) {
+        self.finished = true;
+    }
+
+    // Returns the next complete value, or `None` if more chunks are needed.
+    // After an error the invalid value is skipped, so parsing can continue with the next one.
+    pub fn next_value(&mut self) -> Result<Option<Json>, Error> {
+        match self.scan() {
+            None => Ok(None),
+            Some(end) => self.parse_value(end).map(Some),
+        }
+    }
+
+    // Scans new bytes, returns the end of the value once it is complete
+    fn scan(&mut self) -> Option<usize> {
+        while self.scanned < self.buffer.len() {
+            let byte = self.buffer[self.scanned];
+            self.scanned += 1;
+
+            self.state = match self.state {
+                State::Between => match byte {
+                    byte if byte.is_ascii_whitespace() => State::Between,
+                    b'[' | b'{' => self.start_value(1, false),
+                    b'"' => self.start_value(0, true),
+                    b']' | b'}' | b',' | b':' => {
+                        // Cannot start a value, let the parser report it
+                        self.value_start = self.scanned - 1;
+                        return Some(self.scanned);
+                    }
+                    _ => {
+                        self.value_start = self.scanned - 1;
+                        State::Scalar
+                    }
+                },
+                State::Scalar => match byte {
+                    b'[' | b'{' | b']' | b'}' | b',' | b':' | b'"' => {
+                        // The delimiter belongs to the next value
+                        self.scanned -= 1;
+                        return Some(self.scanned);
+                    }
+                    byte if byte.is_ascii_whitespace() => return Some(self.scanned - 1),
+                    _ => State::Scalar,
+                },
+                State::Nested {
+                    depth,
+                    in_string: true,
+                    escaped,
+                } => State::Nested {
+                    depth,
+                    in_string: escaped || byte != b'"',
+                    escaped: !escaped && byte == b'\\',
+                },
+                State::Nested { depth, .. } => {
+                    let depth = match byte {
+                        b'[' | b'{' => depth + 1,
+                        b']' | b'}' => depth - 1,
+                        _ => depth,
+                    };
+
+                    State::Nested {
+                        depth,
+                        in_string: byte == b'"',
+                        escaped: false,
+                    }
+                }
+            };
+
+            if let State::Nested {
+                depth: 0,
+                in_string: false,
+                ..
+            } = self.state
+            {
+                return Some(self.scanned);
+            }
+        }
+
+        match self.state {
+            State::Between => {
+                // Only whitespace is left, which does not need to be kept
+                self.consume(self.buffer.len());
+                None
+            }
+            _ if self.finished => Some(self.buffer.len()),
+            _ => None,
+        }
+    }
+
+    fn start_value(&mut self, depth: usize, in_string: bool) -> State {
+        self.value_start = self.scanned - 1;
+
+        State::Nested {
+            depth,
+            in_string,
+            escaped: false,
+        }
+    }
+
+    // Parses the scanned value and removes it from the buffer, also if it is invalid
+    fn parse_value(&mut self, end: usize) -> Result<Json, Error> {
+        let start = self
+            .position
+            .advance(&self.buffer[self.consumed..self.value_start]);
+        let bytes = &self.buffer[self.value_start..end];
+
+        let parsed = match std::str::from_utf8(bytes) {
+            Ok(text) => parse(text, self.options.clone()).map_err(|error| error.shift(start)),
+            Err(error) => {
+                let error = TokenizeError::invalid_utf8(bytes, error);
+                Err(Error::Tokenize(error).shift(start))
+            }
+        };
+
+        self.consume(end);
+        self.state = State::Between;
+        parsed
+    }
+
+    // Marks the bytes up to `end` as parsed
+    fn consume(&mut self, end: usize) {
+        self.position = self.position.advance(&self.buffer[self.consumed..end]);
+        self.consumed = end;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::Error;
+    use crate::parser::{parse, Json, ParseError, ParseOptions};
+    use crate::push::PushParser;
+    use crate::span::Span;
+    use crate::tokenizer::TokenizeError;
+
+    // Feeds all chunks, then finishes the stream and collects every value
+    fn push(chunks: &[&[u8]]) -> Vec<Result<Json, Error>> {
+        let mut parser = PushParser::new(ParseOptions::default());
+        let mut values = vec![];
+
+        for (index, chunk) in chunks.iter().enumerate() {
+            parser.feed(chunk);
+
+            if index == chunks.len() - 1 {
+                parser.finish();
+            }
+
+            while let Some(value) = parser.next_value().transpose() {
+                values.push(value);
+            }
+        }
+
+        values
+    }
+
+    #[test]
+    fn test_push_every_split() {
+        let input = r#"{"a\"b": [true, false, null, -1.5e3, "é😀 éé😀"], "c": {}}"#;
+        for split in 0..=input.len() {
+            let (first, second) = input.as_bytes().split_at(split);
+            let expected = parse(input, ParseOptions::default());
+            assert_eq!(push(&[first, second]), vec![expected], "{}", split);
+        }
+    }
+
+    #[test]
+    fn test_push_byte_by_byte() {
+        let input = "[1] \"two\"\n{\"three\": 3} 4";
+        let chunks: Vec<&[u8]> = input.as_bytes().chunks(1).collect();
+
+        assert_eq!(
+            push(&chunks),
+            vec![
+                parse("[1]", ParseOptions::default()),
+                parse("\"two\"", ParseOptions::default()),
+                parse("{\"three\": 3}", ParseOptions::default()),
+                parse("4", ParseOptions::default()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_push_values_without_whitespace() {
+        assert_eq!(
+            push(&[b"1[2]\"3\"{}true"]),
+            vec![
+                Ok(Json::Number(1.into())),
+                Ok(Json::Array(vec![Json::Number(2.into())])),
+                Ok(Json::String("3".to_owned())),
+                parse("{}", ParseOptions::default()),
+                Ok(Json::Boolean(true)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_push_number_needs_delimiter() {
+        let mut parser = PushParser::new(ParseOptions::default());
+
+        parser.feed(b"12");
+        assert_eq!(parser.next_value(), Ok(None));
+        parser.feed(b"3 ");
+        assert_eq!(parser.next_value(), Ok(Some(Json::Number(123.into()))));
+
+        parser.feed(b"45");
+        assert_eq!(parser.next_value(), Ok(None));
+        parser.finish();
+        assert_eq!(parser.next_value(), Ok(Some(Json::Number(45.into()))));
+        assert_eq!(parser.next_value(), Ok(None));
+    }
+
+    #[test]
+    fn test_push_error_position() {
+        assert_eq!(
+            push(&[b"[1]\n[2,", b" x]", b" [3]"]),
+            vec![
+                Ok(Json::Array(vec![Json::Number(1.into())])),
+                Err(Error::Tokenize(TokenizeError {
+                    span: Span {
+                        offset: 8,
+                        len: 1,
+                        line: 2,
+                        column: 5
+                    },
+                    message: "Unhandled character".to_owned(),
+                })),
+                Ok(Json::Array(vec![Json::Number(3.into())])),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_push_cut_off() {
+        assert_eq!(
+            push(&[b"{\"a\": [1, "]),
+            vec![Err(Error::Parse(ParseError {
+                span: Span {
+                    offset: 10,
+                    len: 0,
+                    line: 1,
+                    column: 11
+                },
+                message: "Unexpected end of input".to_owned(),
+            }))]
+        );
+    }
+
+    #[test]
+    fn test_push_invalid_utf8() {
+        assert_eq!(
+            push(&[b"\"ok\" \"\xc3", b"\xa9\xff\""]),
+            vec![
+                Ok(Json::String("ok".to_owned())),
+                Err(Error::Tokenize(TokenizeError {
+                    span: Span {
+                        offset: 8,
+                        len: 1,
+                        line: 1,
+                        column: 8
+                    },
+                    message: "Invalid UTF-8".to_owned(),
+                })),
+            ]
+        );
+    }
+}
@@ -0,0 +1,180 @@
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+use crate::error::Error;
+use crate::parser::{self, Json, ParseOptions};
+use crate::span::Span;
+use crate::tokenizer::TokenizeError;
+
+// Some editors on Windows start UTF-8 files with a byte order mark, which is not part of the JSON
+pub(crate) const BOM: &[u8] = b"\xef\xbb\xbf";
+
+// Reading the input failed, before any JSON could be parsed
+#[derive(Debug)]
+pub struct ReadError {
+    // Position in the input up to where it could be read
+    pub span: Span,
+    pub message: String,
+    pub error: io::Error,
+}
+
+impl ReadError {
+    pub(crate) fn new(span: Span, error: io::Error) -> Self {
+        Self {
+            span,
+            message: format!("Cannot read input: {}", error),
+            error,
+        }
+    }
+}
+
+// `io::Error` can't be compared, so only its kind is
+impl PartialEq for ReadError {
+    fn eq(&self, other: &Self) -> bool {
+        self.span == other.span
+            && self.message == other.message
+            && self.error.kind() == other.error.kind()
+    }
+}
+
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.span, self.message)
+    }
+}
+
+impl std::error::Error for ReadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+// Reads all of `reader` into one buffer before parsing it, so the whole input is held in memory
+// next to the tree that is built from it. Use `PushParser` to parse values while they arrive.
+// A leading byte order mark is skipped, error positions still count its bytes.
+pub fn parse<R: Read>(mut reader: R, options: ParseOptions) -> Result<Json, Error> {
+    let mut bytes = vec![];
+
+    if let Err(error) = reader.read_to_end(&mut bytes) {
+        let span = Span::new("", 0, 0).advance(&bytes);
+        return Err(Error::Read(ReadError::new(span, error)));
+    }
+
+    let text = bytes.strip_prefix(BOM).unwrap_or(&bytes);
+    let start = Span::new("", bytes.len() - text.len(), 0);
+
+    let parsed = match std::str::from_utf8(text) {
+        Ok(text) => parser::parse(text, options),
+        Err(error) => Err(Error::Tokenize(TokenizeError::invalid_utf8(text, error))),
+    };
+
+    parsed.map_err(|error| error.shift(start))
+}
+
+// Reads the whole file into memory, like `parse`
+pub fn parse_file<P: AsRef<Path>>(path: P, options: ParseOptions) -> Result<Json, Error> {
+    match File::open(path) {
+        Ok(file) => parse(file, options),
+        Err(error) => Err(Error::Read(ReadError::new(Span::new("", 0, 0), error))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Read};
+
+    use crate::error::Error;
+    use crate::parser::{Json, ParseError, ParseOptions};
+    use crate::reader::{parse, parse_file};
+    use crate::span::Span;
+    use crate::tokenizer::TokenizeError;
+
+    macro_rules! reader_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (input, expected): (&[u8], _) = $value;
+                assert_eq!(parse(input, ParseOptions::default()), expected);
+            }
+        )*
+        }
+    }
+
+    reader_tests! {
+        test_reader_value: (b" [null] ", Ok(Json::Array(vec![Json::Null]))),
+        test_reader_bom: (b"\xef\xbb\xbftrue", Ok(Json::Boolean(true))),
+        test_reader_bom_only_at_start: (
+                b"[\"\xef\xbb\xbf\"]",
+                Ok(Json::Array(vec![Json::String("\u{feff}".to_owned())])),
+            ),
+        test_reader_invalid_utf8: (
+                b"[\"a\",\n \"\xe9t\xe9\"]",
+                Err(Error::Tokenize(TokenizeError {
+                    span: Span { offset: 8, len: 1, line: 2, column: 3 },
+                    message: "Invalid UTF-8".to_owned(),
+                })),
+            ),
+        test_reader_cut_off_utf8: (
+                b"\"\xc3",
+                Err(Error::Tokenize(TokenizeError {
+                    span: Span { offset: 1, len: 1, line: 1, column: 2 },
+                    message: "Invalid UTF-8".to_owned(),
+                })),
+            ),
+        test_reader_error_after_bom: (
+                b"\xef\xbb\xbf[1 2]",
+                Err(Error::Parse(ParseError {
+                    span: Span { offset: 6, len: 1, line: 1, column: 4 },
+                    message: "Unexpected token `2` in array".to_owned(),
+                })),
+            ),
+    }
+
+    // Returns its bytes one at a time, then fails
+    struct FailingReader(&'static [u8]);
+
+    impl Read for FailingReader {
+        fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+            match self.0.split_first() {
+                Some((&byte, rest)) => {
+                    buffer[0] = byte;
+                    self.0 = rest;
+                    Ok(1)
+                }
+                None => Err(io::Error::new(io::ErrorKind::ConnectionReset, "reset")),
+            }
+        }
+    }
+
+    #[test]
+    fn test_reader_io_error() {
+        let error = parse(FailingReader(b"[1,\n2"), ParseOptions::default()).unwrap_err();
+
+        assert_eq!(error.to_string(), "2:2: Cannot read input: reset");
+        assert_eq!(error.span().offset, 5);
+    }
+
+    #[test]
+    fn test_parse_file() {
+        let path = std::env::temp_dir().join("rust_playground_test_parse_file.json");
+        std::fs::write(&path, b"\xef\xbb\xbf{\"a\": [1.5]}").unwrap();
+
+        let parsed = parse_file(&path, ParseOptions::default());
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(parsed.unwrap().to_string(), "{\"a\":[1.5]}");
+    }
+
+    #[test]
+    fn test_parse_file_missing() {
+        let error = parse_file("/does/not/exist.json", ParseOptions::default()).unwrap_err();
+
+        let Error::Read(error) = error else {
+            panic!("Expected read error, found {:?}", error);
+        };
+        assert_eq!(error.error.kind(), io::ErrorKind::NotFound);
+    }
+}
@@ -0,0 +1,225 @@
+use crate::error::Error;
+use crate::parser::{parse, Json, ParseError, ParseOptions, Parser};
+use crate::span::Span;
+
+// Starts every record of a JSON text sequence
+const RECORD_SEPARATOR: char = '\u{1e}';
+
+// Parses values that follow each other, with or without whitespace in between, like `{"a":1}{"b":2}`.
+// Stops after the first error, because it is unclear where the next value would start.
+pub struct Concatenated<'a> {
+    parser: Parser<'a>,
+    done: bool,
+}
+
+impl<'a> Iterator for Concatenated<'a> {
+    type Item = Result<Json, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let parsed = self.parser.parse_next().transpose();
+        self.done = !matches!(parsed, Some(Ok(_)));
+        parsed
+    }
+}
+
+pub fn parse_concatenated(input: &str, options: ParseOptions) -> Concatenated<'_> {
+    Concatenated {
+        parser: Parser::concatenated(input, options),
+        done: false,
+    }
+}
+
+// Parses a JSON text sequence (RFC 7464), in which every value is preceded by an ASCII record separator.
+// Records that can't be parsed, for example because they were cut off, are reported and skipped.
+pub struct JsonSeq<'a> {
+    input: &'a str,
+    options: ParseOptions,
+    // Offset of the next record, including its separator
+    offset: usize,
+    // Position of `offset` in the input
+    position: Span,
+}
+
+impl<'a> JsonSeq<'a> {
+    fn parse_record(&self, text: &str, start: Span) -> Result<Json, Error> {
+        let json = parse(text, self.options.clone()).map_err(|error| error.shift(start))?;
+
+        // A number or literal could have been cut off anywhere, unless the record ends with whitespace
+        let unterminated = !text.ends_with(|c: char| c.is_ascii_whitespace());
+        if unterminated && matches!(json, Json::Number(_) | Json::Boolean(_) | Json::Null) {
+            let value = text.trim_start();
+            let span = Span::new(text, text.len() - value.len(), value.len());
+            let message = "Value is not followed by whitespace and may be truncated".to_owned();
+
+            return Err(Error::Parse(ParseError::new(span.shift(start), message)));
+        }
+
+        Ok(json)
+    }
+}
+
+impl<'a> Iterator for JsonSeq<'a> {
+    type Item = Result<Json, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.offset < self.input.len() {
+            let rest = &self.input[self.offset..];
+
+            // Only text before the first separator does not start with one
+            let (separator, text) = match rest.strip_prefix(RECORD_SEPARATOR) {
+                Some(text) => (RECORD_SEPARATOR.len_utf8(), text),
+                None => (0, rest),
+            };
+            let text = &text[..text.find(RECORD_SEPARATOR).unwrap_or(text.len())];
+
+            let start = self.position.advance(&rest.as_bytes()[..separator]);
+            self.offset += separator + text.len();
+            self.position = start.advance(text.as_bytes());
+
+            // Empty records are allowed
+            if text.trim().is_empty() {
+                continue;
+            }
+
+            if separator == 0 {
+                let span = Span::new(text, 0, text.len()).shift(start);
+                let message = "Expected a record separator before the value".to_owned();
+
+                return Some(Err(Error::Parse(ParseError::new(span, message))));
+            }
+
+            return Some(self.parse_record(text, start));
+        }
+
+        None
+    }
+}
+
+pub fn parse_json_seq(input: &str, options: ParseOptions) -> JsonSeq<'_> {
+    JsonSeq {
+        input,
+        options,
+        offset: 0,
+        position: Span::new("", 0, 0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::Error;
+    use crate::map::Map;
+    use crate::parser::{Json, ParseError, ParseOptions};
+    use crate::sequence::{parse_concatenated, parse_json_seq};
+    use crate::span::Span;
+
+    fn object(key: &str, value: i32) -> Json {
+        Json::Object(Map::from([(key.to_owned(), Json::Number(value.into()))]))
+    }
+
+    #[test]
+    fn test_concatenated() {
+        let values: Vec<_> =
+            parse_concatenated("{\"a\":1}{\"b\":2}\n3 \"x\"[]", ParseOptions::default()).collect();
+
+        assert_eq!(
+            values,
+            vec![
+                Ok(object("a", 1)),
+                Ok(object("b", 2)),
+                Ok(Json::Number(3.into())),
+                Ok(Json::String("x".to_owned())),
+                Ok(Json::Array(vec![])),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_concatenated_empty() {
+        assert_eq!(parse_concatenated("  ", ParseOptions::default()).count(), 0);
+    }
+
+    #[test]
+    fn test_concatenated_stops_at_error() {
+        let values: Vec<_> = parse_concatenated("[1] [2 3] [4]", ParseOptions::default()).collect();
+
+        assert_eq!(
+            values,
+            vec![
+                Ok(Json::Array(vec![Json::Number(1.into())])),
+                Err(Error::Parse(ParseError {
+                    span: Span {
+                        offset: 7,
+                        len: 1,
+                        line: 1,
+                        column: 8
+                    },
+                    message: "Unexpected token `3` in array".to_owned(),
+                })),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_json_seq() {
+        let input = "\u{1e}{\"a\":1}\n\u{1e}\u{1e}\n\u{1e}2\n";
+        let values: Vec<_> = parse_json_seq(input, ParseOptions::default()).collect();
+
+        assert_eq!(values, vec![Ok(object("a", 1)), Ok(Json::Number(2.into()))]);
+    }
+
+    #[test]
+    fn test_json_seq_truncated_records() {
+        let input = "\u{1e}{\"a\":\u{1e}{\"b\":2}\n\u{1e}12";
+        let values: Vec<_> = parse_json_seq(input, ParseOptions::default()).collect();
+
+        assert_eq!(
+            values,
+            vec![
+                Err(Error::Parse(ParseError {
+                    span: Span {
+                        offset: 6,
+                        len: 0,
+                        line: 1,
+                        column: 7
+                    },
+                    message: "Unexpected end of input".to_owned(),
+                })),
+                Ok(object("b", 2)),
+                Err(Error::Parse(ParseError {
+                    span: Span {
+                        offset: 16,
+                        len: 2,
+                        line: 2,
+                        column: 2
+                    },
+                    message: "Value is not followed by whitespace and may be truncated".to_owned(),
+                })),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_json_seq_missing_separator() {
+        let values: Vec<_> = parse_json_seq("1\n\u{1e}2\n", ParseOptions::default()).collect();
+
+        assert_eq!(
+            values,
+            vec![
+                Err(Error::Parse(ParseError {
+                    span: Span {
+                        offset: 0,
+                        len: 2,
+                        line: 1,
+                        column: 1
+                    },
+                    message: "Expected a record separator before the value".to_owned(),
+                })),
+                Ok(Json::Number(2.into())),
+            ]
+        );
+    }
+}
@@ -0,0 +1,135 @@
+use std::fmt::{self, Write};
+use std::io;
+
+use crate::parser::Json;
+
+pub(crate) fn write_string<W: Write>(output: &mut W, s: &str) -> fmt::Result {
+    output.write_char('"')?;
+
+    for c in s.chars() {
+        match c {
+            '"' => output.write_str("\\\"")?,
+            '\\' => output.write_str("\\\\")?,
+            '\u{8}' => output.write_str("\\b")?,
+            '\u{c}' => output.write_str("\\f")?,
+            '\n' => output.write_str("\\n")?,
+            '\r' => output.write_str("\\r")?,
+            '\t' => output.write_str("\\t")?,
+            c if c < ' ' => write!(output, "\\u{:04x}", c as u32)?,
+            c => output.write_char(c)?,
+        }
+    }
+
+    output.write_char('"')
+}
+
+pub(crate) fn write_json<W: Write>(output: &mut W, json: &Json) -> fmt::Result {
+    match json {
+        Json::Null => output.write_str("null"),
+        Json::Boolean(true) => output.write_str("true"),
+        Json::Boolean(false) => output.write_str("false"),
+        Json::Number(number) => output.write_str(number.as_str()),
+        Json::String(s) => write_string(output, s),
+        Json::Array(array) => {
+            output.write_char('[')?;
+            for (index, item) in array.iter().enumerate() {
+                if index > 0 {
+                    output.write_char(',')?;
+                }
+                write_json(output, item)?;
+            }
+            output.write_char(']')
+        }
+        Json::Object(object) => {
+            output.write_char('{')?;
+            for (index, (key, value)) in object.iter().enumerate() {
+                if index > 0 {
+                    output.write_char(',')?;
+                }
+                write_string(output, key)?;
+                output.write_char(':')?;
+                write_json(output, value)?;
+            }
+            output.write_char('}')
+        }
+    }
+}
+
+// Formats as compact JSON
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_json(f, self)
+    }
+}
+
+pub fn to_string(json: &Json) -> String {
+    json.to_string()
+}
+
+// Writes compact JSON, wrap `writer` in a `std::io::BufWriter` when it does unbuffered writes
+pub fn to_writer<W: io::Write>(mut writer: W, json: &Json) -> io::Result<()> {
+    write!(writer, "{}", json)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::map::Map;
+    use crate::number::Number;
+    use crate::parser::Json;
+    use crate::serializer::{to_string, to_writer};
+
+    macro_rules! serializer_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (json, expected) = $value;
+                assert_eq!(to_string(&json), expected);
+            }
+        )*
+        }
+    }
+
+    serializer_tests! {
+        test_serialize_null: (Json::Null, "null"),
+        test_serialize_true: (Json::Boolean(true), "true"),
+        test_serialize_false: (Json::Boolean(false), "false"),
+        test_serialize_integer: (Json::Number(3.into()), "3"),
+        test_serialize_decimal: (Json::Number("-13.37".parse().unwrap()), "-13.37"),
+        test_serialize_string: (Json::String("hello".to_owned()), "\"hello\""),
+        test_serialize_string_escapes: (
+                Json::String("\"\\/\u{8}\u{c}\n\r\t\u{1}é".to_owned()),
+                "\"\\\"\\\\/\\b\\f\\n\\r\\t\\u0001é\"",
+            ),
+        test_serialize_array: (
+                Json::Array(vec![Json::Number(1.into()), Json::Null, Json::Array(vec![])]),
+                "[1,null,[]]",
+            ),
+        test_serialize_object: (
+                Json::Object(Map::from([("a".to_owned(), Json::Array(vec![Json::Boolean(true)]))])),
+                "{\"a\":[true]}",
+            ),
+        test_serialize_empty_object: (Json::Object(Map::new()), "{}"),
+        test_serialize_float: (Json::Number(Number::from_f64(1e300).unwrap()), "1e300"),
+        test_serialize_keeps_lexeme: (Json::Number("-0.50E+02".parse().unwrap()), "-0.50E+02"),
+        test_serialize_large_integer: (Json::Number(u64::MAX.into()), "18446744073709551615"),
+    }
+
+    #[test]
+    fn test_display() {
+        let json = Json::Array(vec![
+            Json::String("a\"b".to_owned()),
+            Json::Number("2.5".parse().unwrap()),
+        ]);
+        assert_eq!(format!("{}", json), "[\"a\\\"b\",2.5]");
+    }
+
+    #[test]
+    fn test_to_writer() {
+        let json = Json::Object(Map::from([("key".to_owned(), Json::Null)]));
+        let mut output = vec![];
+
+        to_writer(&mut output, &json).unwrap();
+        assert_eq!(output, b"{\"key\":null}");
+    }
+}
@@ -0,0 +1,137 @@
+use std::fmt;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Span {
+    // Byte offset and byte length in the input
+    pub offset: usize,
+    pub len: usize,
+    // Both start at 1, columns are counted in characters
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Span {
+    pub fn new(input: &str, offset: usize, len: usize) -> Self {
+        let mut line = 1;
+        let mut column = 1;
+
+        for (index, c) in input.char_indices() {
+            if index >= offset {
+                break;
+            }
+
+            if c == '\n' {
+                line += 1;
+                column = 1;
+            } else {
+                column += 1;
+            }
+        }
+
+        Self {
+            offset,
+            len,
+            line,
+            column,
+        }
+    }
+
+    // Converts a span found in a part of a larger input into a span in that input,
+    // `start` is the position where the part begins
+    pub(crate) fn shift(self, start: Span) -> Self {
+        let column = match self.line {
+            1 => start.column + self.column - 1,
+            _ => self.column,
+        };
+
+        Self {
+            offset: start.offset + self.offset,
+            len: self.len,
+            line: start.line + self.line - 1,
+            column,
+        }
+    }
+
+    // Moves a zero length span past `bytes`, which may end in the middle of a character
+    pub(crate) fn advance(self, bytes: &[u8]) -> Self {
+        let mut line = self.line;
+        let mut column = self.column;
+
+        for &byte in bytes {
+            if byte == b'\n' {
+                line += 1;
+                column = 1;
+            } else if byte & 0xc0 != 0x80 {
+                // Count the first byte of every character
+                column += 1;
+            }
+        }
+
+        Self {
+            offset: self.offset + bytes.len(),
+            len: 0,
+            line,
+            column,
+        }
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::span::Span;
+
+    macro_rules! span_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (input, offset, expected) = $value;
+                let span = Span::new(input, offset, 1);
+                assert_eq!((span.line, span.column), expected);
+            }
+        )*
+        }
+    }
+
+    span_tests! {
+        test_span_start: ("[1, 2]", 0, (1, 1)),
+        test_span_first_line: ("[1, 2]", 4, (1, 5)),
+        test_span_second_line: ("[1,\n 2]", 5, (2, 2)),
+        test_span_after_crlf: ("[1,\r\n2]", 5, (2, 1)),
+        test_span_non_ascii: ("[\"é\", 2]", 6, (1, 6)),
+        test_span_end_of_input: ("[1,\n", 4, (2, 1)),
+    }
+
+    #[test]
+    fn test_span_shift() {
+        let start = Span::new("ab\ncd", 5, 0);
+
+        assert_eq!(
+            Span::new("x", 0, 1).shift(start),
+            Span::new("ab\ncdx", 5, 1)
+        );
+        assert_eq!(
+            Span::new("x\nyz", 3, 1).shift(start),
+            Span::new("ab\ncdx\nyz", 8, 1)
+        );
+    }
+
+    #[test]
+    fn test_span_advance() {
+        let input = "[\"é\",\n 2]";
+        let span = Span::new(input, 0, 0).advance(&input.as_bytes()[..8]);
+
+        assert_eq!(span, Span::new(input, 8, 0));
+    }
+
+    #[test]
+    fn test_span_display() {
+        assert_eq!(Span::new("{\n\n  x", 5, 1).to_string(), "3:3");
+    }
+}
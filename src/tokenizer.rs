@@ -1,6 +1,10 @@
-use regex::Regex;
+use std::borrow::Cow;
+use std::fmt;
+use std::str::Utf8Error;
 
-#[derive(Debug, PartialEq, Clone)]
+use crate::span::Span;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum TokenType {
     ArrayEnd,
     ArrayStart,
@@ -18,28 +22,50 @@ pub enum TokenType {
 
 #[derive(Debug, PartialEq)]
 pub struct TokenizeError {
-    pub offset: usize,
+    pub span: Span,
     pub message: String,
 }
 
 impl TokenizeError {
-    fn new(offset: usize, message: String) -> Self {
-        Self { offset, message }
+    pub(crate) fn new(span: Span, message: String) -> Self {
+        Self { span, message }
+    }
+
+    // Points at the first byte of `bytes` that is not valid UTF-8
+    pub(crate) fn invalid_utf8(bytes: &[u8], error: Utf8Error) -> Self {
+        let valid = &bytes[..error.valid_up_to()];
+        let start = Span::new("", 0, 0);
+        let span = Span {
+            // A character that is cut off by the end of input spans the remaining bytes
+            len: error.error_len().unwrap_or(bytes.len() - valid.len()),
+            ..start.advance(valid)
+        };
+
+        Self::new(span, "Invalid UTF-8".to_owned())
     }
 }
 
-#[derive(Debug, PartialEq, Clone)]
-pub struct Token {
+impl fmt::Display for TokenizeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.span, self.message)
+    }
+}
+
+impl std::error::Error for TokenizeError {}
+
+// Tokens borrow their text from the input, so tokenizing doesn't allocate
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Token<'a> {
     pub type_: TokenType,
-    pub value: String,
+    pub value: &'a str,
     pub offset: usize,
 }
 
-impl Token {
-    pub fn new(type_: TokenType, value: &str, offset: usize) -> Self {
+impl<'a> Token<'a> {
+    pub fn new(type_: TokenType, value: &'a str, offset: usize) -> Self {
         Self {
             type_,
-            value: value.to_owned(),
+            value,
             offset,
         }
     }
@@ -47,130 +73,198 @@ impl Token {
     pub fn len(&self) -> usize {
         self.value.len()
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.value.is_empty()
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct TokenizeOptions {
+    // Reject raw control characters and unknown escape sequences inside strings
+    pub strict: bool,
 }
 
-struct Tokenizer {
-    input: String,
+impl Default for TokenizeOptions {
+    fn default() -> Self {
+        Self { strict: true }
+    }
+}
+
+// States of the number scanner, named after the last part of the number that was read
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum NumberState {
+    Start,
+    Minus,
+    Zero,
+    Integer,
+    Point,
+    Fraction,
+    Exponent,
+    ExponentSign,
+    ExponentDigits,
+}
+
+impl NumberState {
+    // Returns `None` if `byte` can't continue the number
+    fn next(self, byte: u8) -> Option<Self> {
+        match (self, byte) {
+            (NumberState::Start, b'-') => Some(NumberState::Minus),
+            (NumberState::Start | NumberState::Minus, b'0') => Some(NumberState::Zero),
+            (NumberState::Start | NumberState::Minus, b'1'..=b'9') => Some(NumberState::Integer),
+            (NumberState::Integer, b'0'..=b'9') => Some(NumberState::Integer),
+            (NumberState::Zero | NumberState::Integer, b'.') => Some(NumberState::Point),
+            (NumberState::Point | NumberState::Fraction, b'0'..=b'9') => {
+                Some(NumberState::Fraction)
+            }
+            (NumberState::Zero | NumberState::Integer | NumberState::Fraction, b'e' | b'E') => {
+                Some(NumberState::Exponent)
+            }
+            (NumberState::Exponent, b'+' | b'-') => Some(NumberState::ExponentSign),
+            (
+                NumberState::Exponent | NumberState::ExponentSign | NumberState::ExponentDigits,
+                b'0'..=b'9',
+            ) => Some(NumberState::ExponentDigits),
+            _ => None,
+        }
+    }
+}
+
+pub struct Tokenizer<'a> {
+    input: &'a str,
+    // Byte offset of the next token, always on a char boundary
     offset: usize,
-    tokens: Vec<Token>,
+    options: TokenizeOptions,
 }
 
-impl Tokenizer {
-    fn new(input: &str) -> Self {
+impl<'a> Tokenizer<'a> {
+    pub fn new(input: &'a str, options: TokenizeOptions) -> Self {
         Self {
-            input: input.to_owned(),
+            input,
             offset: 0,
-            tokens: vec![],
+            options,
         }
     }
 
-    fn tokenize(mut self) -> Result<Vec<Token>, TokenizeError> {
-        loop {
-            let c = self.input.chars().nth(self.offset);
-
-            let token_result = match c {
-                None => break,
-                Some(',') => self.tokenize_literal(",", TokenType::Comma),
-                Some(':') => self.tokenize_literal(":", TokenType::Colon),
-                Some('[') => self.tokenize_literal("[", TokenType::ArrayStart),
-                Some(']') => self.tokenize_literal("]", TokenType::ArrayEnd),
-                Some('{') => self.tokenize_literal("{", TokenType::ObjectStart),
-                Some('}') => self.tokenize_literal("}", TokenType::ObjectEnd),
-                Some('f') => self.tokenize_literal("false", TokenType::False),
-                Some('n') => self.tokenize_literal("null", TokenType::Null),
-                Some('t') => self.tokenize_literal("true", TokenType::True),
-                Some('"') => self.tokenize_string(),
-                Some('-') => self.tokenize_number(),
-                Some(c) => {
-                    if c.is_ascii_digit() {
-                        self.tokenize_number()
-                    } else if c.is_ascii_whitespace() {
-                        self.tokenize_whitespace()
-                    } else {
-                        Err(TokenizeError::new(
-                            self.offset,
-                            "Unhandled character".to_owned(),
-                        ))
-                    }
-                }
-            };
+    // Moves past whitespace without creating a token for it
+    pub fn skip_whitespace(&mut self) {
+        self.offset += self.whitespace_len();
+    }
+
+    fn whitespace_len(&self) -> usize {
+        self.input.as_bytes()[self.offset..]
+            .iter()
+            .take_while(|b| b.is_ascii_whitespace())
+            .count()
+    }
 
-            match token_result {
-                Ok(token) => {
-                    self.offset += token.len();
-                    self.tokens.push(token);
+    fn next_token(&self, c: char) -> Result<Token<'a>, TokenizeError> {
+        match c {
+            ',' => self.tokenize_literal(",", TokenType::Comma),
+            ':' => self.tokenize_literal(":", TokenType::Colon),
+            '[' => self.tokenize_literal("[", TokenType::ArrayStart),
+            ']' => self.tokenize_literal("]", TokenType::ArrayEnd),
+            '{' => self.tokenize_literal("{", TokenType::ObjectStart),
+            '}' => self.tokenize_literal("}", TokenType::ObjectEnd),
+            'f' => self.tokenize_literal("false", TokenType::False),
+            'n' => self.tokenize_literal("null", TokenType::Null),
+            't' => self.tokenize_literal("true", TokenType::True),
+            '"' => self.tokenize_string(),
+            '-' | '.' => self.tokenize_number(),
+            c => {
+                if c.is_ascii_digit() {
+                    self.tokenize_number()
+                } else if c.is_ascii_whitespace() {
+                    self.tokenize_whitespace()
+                } else {
+                    Err(self.error(c.len_utf8(), "Unhandled character".to_owned()))
                 }
-                Err(offset) => return Err(offset),
             }
         }
-
-        Ok(self.tokens)
     }
 
     fn tokenize_literal(
         &self,
         literal: &'static str,
         type_: TokenType,
-    ) -> Result<Token, TokenizeError> {
-        if self.input.split_at(self.offset).1.starts_with(literal) {
+    ) -> Result<Token<'a>, TokenizeError> {
+        if self.input[self.offset..].starts_with(literal) {
             let token = Token::new(type_, literal, self.offset);
             return Ok(token);
         }
-        Err(TokenizeError::new(
-            self.offset,
-            format!("Expected literal `{}`", literal),
-        ))
+        Err(self.error(1, format!("Expected literal `{}`", literal)))
     }
 
-    fn tokenize_number(&self) -> Result<Token, TokenizeError> {
-        let re = Regex::new(r"-?(?:0|[1-9]\d*)(?:\.\d+)?(?:[eE][+-]?\d+)?").unwrap();
+    fn tokenize_number(&self) -> Result<Token<'a>, TokenizeError> {
+        let bytes = self.input.as_bytes();
+        let mut state = NumberState::Start;
+        let mut end = self.offset;
+
+        while let Some(next) = bytes.get(end).and_then(|byte| state.next(*byte)) {
+            state = next;
+            end += 1;
+        }
 
-        let found = re.find_at(&self.input, self.offset);
+        let next_byte = bytes.get(end).copied();
 
-        match found {
-            None => Err(TokenizeError::new(
-                self.offset,
-                "Cannot parse number".to_owned(),
-            )),
-            Some(found) => {
-                let value = found.as_str();
-                Ok(Token::new(TokenType::Number, value, self.offset))
+        let message = match state {
+            NumberState::Zero if next_byte.is_some_and(|byte| byte.is_ascii_digit()) => {
+                return Err(self.error_at(
+                    end - 1,
+                    1,
+                    "Leading zeros are not allowed in numbers".to_owned(),
+                ));
             }
-        }
+            NumberState::Zero
+            | NumberState::Integer
+            | NumberState::Fraction
+            | NumberState::ExponentDigits => {
+                let value = &self.input[self.offset..end];
+                return Ok(Token::new(TokenType::Number, value, self.offset));
+            }
+            NumberState::Start if next_byte == Some(b'.') => "Expected digit before decimal point",
+            NumberState::Start => "Expected digit",
+            NumberState::Minus => "Expected digit after `-`",
+            NumberState::Point => "Expected digit after decimal point",
+            NumberState::Exponent | NumberState::ExponentSign => "Expected digit in exponent",
+        };
+
+        let len = self.input[end..].chars().next().map_or(0, char::len_utf8);
+        Err(self.error_at(end, len, message.to_owned()))
     }
 
-    fn tokenize_string(&self) -> Result<Token, TokenizeError> {
-        let quote_distance = self
-            .input
-            .chars()
-            .skip(self.offset + 1)
-            .position(|x| x == '"');
-
-        match quote_distance {
-            None => Err(TokenizeError::new(
-                self.offset,
-                "No string-terminating quote found".to_owned(),
-            )),
-            Some(quote_distance) => {
-                let str_end_offset = self.offset + quote_distance + 2;
-                let value = &self.input[self.offset..str_end_offset];
-                let token = Token::new(TokenType::String, value, self.offset);
-                Ok(token)
+    fn tokenize_string(&self) -> Result<Token<'a>, TokenizeError> {
+        // Multi-byte UTF-8 sequences never contain ASCII bytes, so scanning bytes is safe
+        let bytes = self.input.as_bytes();
+        let mut end = self.offset + 1;
+
+        loop {
+            match bytes.get(end) {
+                None => return Err(self.error(1, "No string-terminating quote found".to_owned())),
+                Some(b'"') => break,
+                // Skip escaped character, so an escaped quote doesn't end the string
+                Some(b'\\') => end += 2,
+                Some(_) => end += 1,
             }
         }
+
+        let value = &self.input[self.offset..end + 1];
+        let token = Token::new(TokenType::String, value, self.offset);
+        validate(self.input, &token, self.options.strict)?;
+
+        Ok(token)
     }
 
-    fn tokenize_whitespace(&self) -> Result<Token, TokenizeError> {
-        let mut ws_end_offset = self.offset;
-        let chars = self.input.chars().skip(self.offset);
+    fn error(&self, len: usize, message: String) -> TokenizeError {
+        self.error_at(self.offset, len, message)
+    }
 
-        for c in chars {
-            if c.is_ascii_whitespace() {
-                ws_end_offset += 1;
-            } else {
-                break;
-            }
-        }
+    fn error_at(&self, offset: usize, len: usize, message: String) -> TokenizeError {
+        TokenizeError::new(Span::new(self.input, offset, len), message)
+    }
+
+    fn tokenize_whitespace(&self) -> Result<Token<'a>, TokenizeError> {
+        let ws_end_offset = self.offset + self.whitespace_len();
 
         let token = Token::new(
             TokenType::Whitespace,
@@ -181,16 +275,206 @@ impl Tokenizer {
     }
 }
 
-pub fn tokenize(input: &str) -> Result<Vec<Token>, TokenizeError> {
-    Tokenizer::new(input).tokenize()
+impl<'a> Iterator for Tokenizer<'a> {
+    type Item = Result<Token<'a>, TokenizeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let c = self.input[self.offset..].chars().next()?;
+        let token_result = self.next_token(c);
+
+        match &token_result {
+            Ok(token) => self.offset += token.len(),
+            // Stop after the first error
+            Err(_) => self.offset = self.input.len(),
+        }
+
+        Some(token_result)
+    }
+}
+
+pub fn tokenize(input: &str) -> Result<Vec<Token<'_>>, TokenizeError> {
+    tokenize_with_options(input, TokenizeOptions::default())
+}
+
+pub fn tokenize_with_options(
+    input: &str,
+    options: TokenizeOptions,
+) -> Result<Vec<Token<'_>>, TokenizeError> {
+    Tokenizer::new(input, options).collect()
+}
+
+fn parse_hex_escape<I>(chars: &mut I, input: &str, offset: usize) -> Result<u32, TokenizeError>
+where
+    I: Iterator<Item = (usize, char)>,
+{
+    let mut code_unit = 0;
+
+    for _ in 0..4 {
+        match chars.next().and_then(|(_, c)| c.to_digit(16)) {
+            Some(digit) => code_unit = code_unit * 16 + digit,
+            None => {
+                return Err(TokenizeError::new(
+                    Span::new(input, offset, 2),
+                    "Expected 4 hex digits in `\\u` escape sequence".to_owned(),
+                ))
+            }
+        }
+    }
+
+    Ok(code_unit)
+}
+
+// Decodes a string token found in `input`, errors point into `input`.
+// When not `strict`, raw control characters and unknown escape sequences are kept as-is.
+// Strings without escape sequences are borrowed from the token instead of copied.
+pub fn unescape<'a>(
+    input: &str,
+    token: &Token<'a>,
+    strict: bool,
+) -> Result<Cow<'a, str>, TokenizeError> {
+    let content = &token.value[1..token.value.len() - 1];
+
+    if !needs_decoding(content, strict) {
+        return Ok(Cow::Borrowed(content));
+    }
+
+    let mut unescaped = String::with_capacity(content.len());
+    decode(input, token, strict, Some(&mut unescaped))?;
+
+    Ok(Cow::Owned(unescaped))
+}
+
+// Returns the errors `unescape` would, without building the decoded string
+fn validate(input: &str, token: &Token, strict: bool) -> Result<(), TokenizeError> {
+    let content = &token.value[1..token.value.len() - 1];
+
+    if !needs_decoding(content, strict) {
+        return Ok(());
+    }
+
+    decode(input, token, strict, None)
+}
+
+fn needs_decoding(content: &str, strict: bool) -> bool {
+    let has_control = || content.bytes().any(|b| b.is_ascii_control() && b != 0x7f);
+    content.contains('\\') || (strict && has_control())
+}
+
+// Appends the decoded string to `output`, if any
+fn decode(
+    input: &str,
+    token: &Token,
+    strict: bool,
+    mut output: Option<&mut String>,
+) -> Result<(), TokenizeError> {
+    let offset = token.offset;
+    let content = &token.value[1..token.value.len() - 1];
+    let mut chars = content.char_indices();
+
+    while let Some((index, c)) = chars.next() {
+        if strict && c.is_ascii_control() && c != '\u{7f}' {
+            return Err(TokenizeError::new(
+                Span::new(input, offset + 1 + index, 1),
+                format!("Unescaped control character U+{:04X} in string", c as u32),
+            ));
+        }
+
+        if c != '\\' {
+            if let Some(output) = &mut output {
+                output.push(c);
+            }
+            continue;
+        }
+
+        let escape_offset = offset + 1 + index;
+
+        let decoded = match chars.next().map(|(_, c)| c) {
+            Some('"') => '"',
+            Some('\\') => '\\',
+            Some('/') => '/',
+            Some('b') => '\u{8}',
+            Some('f') => '\u{c}',
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('u') => {
+                let high = parse_hex_escape(&mut chars, input, escape_offset)?;
+
+                let code_point = match high {
+                    0xD800..=0xDBFF => {
+                        let low = match (chars.next(), chars.next()) {
+                            (Some((_, '\\')), Some((_, 'u'))) => {
+                                parse_hex_escape(&mut chars, input, escape_offset + 6)?
+                            }
+                            _ => 0,
+                        };
+
+                        if !(0xDC00..=0xDFFF).contains(&low) {
+                            return Err(TokenizeError::new(
+                                Span::new(input, escape_offset, 6),
+                                format!("Unpaired surrogate `\\u{:04X}`", high),
+                            ));
+                        }
+
+                        0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+                    }
+                    0xDC00..=0xDFFF => {
+                        return Err(TokenizeError::new(
+                            Span::new(input, escape_offset, 6),
+                            format!("Unpaired surrogate `\\u{:04X}`", high),
+                        ))
+                    }
+                    _ => high,
+                };
+
+                // Surrogates are handled above, so this is always a valid char
+                char::from_u32(code_point).unwrap()
+            }
+            Some(c) if !strict => {
+                if let Some(output) = &mut output {
+                    output.push('\\');
+                }
+                c
+            }
+            Some(c) => {
+                return Err(TokenizeError::new(
+                    Span::new(input, escape_offset, 1 + c.len_utf8()),
+                    format!("Invalid escape sequence `\\{}`", c),
+                ))
+            }
+            None => {
+                return Err(TokenizeError::new(
+                    Span::new(input, escape_offset, 1),
+                    "Incomplete escape sequence".to_owned(),
+                ))
+            }
+        };
+
+        if let Some(output) = &mut output {
+            output.push(decoded);
+        }
+    }
+
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::tokenize;
+    use std::borrow::Cow;
+
+    use proptest::prelude::*;
+
+    use crate::parser::Json;
+    use crate::serializer::to_string;
+    use crate::span::Span;
+    use crate::tokenizer::tokenize;
+    use crate::tokenizer::tokenize_with_options;
+    use crate::tokenizer::unescape;
     use crate::tokenizer::Token;
     use crate::tokenizer::TokenType;
     use crate::tokenizer::TokenizeError;
+    use crate::tokenizer::TokenizeOptions;
+    use crate::tokenizer::Tokenizer;
 
     macro_rules! tokenizer_tests {
         ($($name:ident: $value:expr,)*) => {
@@ -253,26 +537,283 @@ mod tests {
         test_tokenize_broken: (
                 "broken",
                 Err(TokenizeError {
-                    offset: 0,
+                    span: Span { offset: 0, len: 1, line: 1, column: 1 },
                     message: "Unhandled character".to_owned(),
                 }),
             ),
         test_tokenize_unclosed_string: (
                 "\"no closing quote",
                 Err(TokenizeError {
-                    offset: 0,
+                    span: Span { offset: 0, len: 1, line: 1, column: 1 },
+                    message: "No string-terminating quote found".to_owned(),
+                }),
+            ),
+        test_tokenize_string_escaped_quote: (
+                r#""a\"b""#,
+                Ok(vec![Token::new(TokenType::String, r#""a\"b""#, 0)]),
+            ),
+        test_tokenize_string_escaped_backslash: (
+                r#"["a\\", 1]"#,
+                Ok(vec![
+                    Token::new(TokenType::ArrayStart, "[", 0),
+                    Token::new(TokenType::String, r#""a\\""#, 1),
+                    Token::new(TokenType::Comma, ",", 6),
+                    Token::new(TokenType::Whitespace, " ", 7),
+                    Token::new(TokenType::Number, "1", 8),
+                    Token::new(TokenType::ArrayEnd, "]", 9),
+                ]),
+            ),
+        test_tokenize_string_invalid_escape: (
+                r#""abc\x""#,
+                Err(TokenizeError {
+                    span: Span { offset: 4, len: 2, line: 1, column: 5 },
+                    message: "Invalid escape sequence `\\x`".to_owned(),
+                }),
+            ),
+        test_tokenize_string_invalid_unicode_escape: (
+                r#""\u12G4""#,
+                Err(TokenizeError {
+                    span: Span { offset: 1, len: 2, line: 1, column: 2 },
+                    message: "Expected 4 hex digits in `\\u` escape sequence".to_owned(),
+                }),
+            ),
+        test_tokenize_string_unpaired_high_surrogate: (
+                r#""ab\ud83d""#,
+                Err(TokenizeError {
+                    span: Span { offset: 3, len: 6, line: 1, column: 4 },
+                    message: "Unpaired surrogate `\\uD83D`".to_owned(),
+                }),
+            ),
+        test_tokenize_string_unpaired_low_surrogate: (
+                r#""\ude00""#,
+                Err(TokenizeError {
+                    span: Span { offset: 1, len: 6, line: 1, column: 2 },
+                    message: "Unpaired surrogate `\\uDE00`".to_owned(),
+                }),
+            ),
+        test_tokenize_string_escaped_closing_quote: (
+                r#""abc\""#,
+                Err(TokenizeError {
+                    span: Span { offset: 0, len: 1, line: 1, column: 1 },
                     message: "No string-terminating quote found".to_owned(),
                 }),
             ),
+        test_tokenize_string_raw_newline: (
+                "\"a\nb\"",
+                Err(TokenizeError {
+                    span: Span { offset: 2, len: 1, line: 1, column: 3 },
+                    message: "Unescaped control character U+000A in string".to_owned(),
+                }),
+            ),
+        test_tokenize_string_raw_nul: (
+                "\"\u{0}\"",
+                Err(TokenizeError {
+                    span: Span { offset: 1, len: 1, line: 1, column: 2 },
+                    message: "Unescaped control character U+0000 in string".to_owned(),
+                }),
+            ),
+        test_tokenize_broken_multiline: (
+                "[1,\n  'a']",
+                Err(TokenizeError {
+                    span: Span { offset: 6, len: 1, line: 2, column: 3 },
+                    message: "Unhandled character".to_owned(),
+                }),
+            ),
+        test_tokenize_string_non_ascii: (
+                "[\"héllo\",\"日本\"]",
+                Ok(vec![
+                    Token::new(TokenType::ArrayStart, "[", 0),
+                    Token::new(TokenType::String, "\"héllo\"", 1),
+                    Token::new(TokenType::Comma, ",", 9),
+                    Token::new(TokenType::String, "\"日本\"", 10),
+                    Token::new(TokenType::ArrayEnd, "]", 18),
+                ]),
+            ),
+        test_tokenize_string_non_ascii_invalid_escape: (
+                r#""éé\x""#,
+                Err(TokenizeError {
+                    span: Span { offset: 5, len: 2, line: 1, column: 4 },
+                    message: "Invalid escape sequence `\\x`".to_owned(),
+                }),
+            ),
+        test_tokenize_non_ascii_unhandled: (
+                "[é]",
+                Err(TokenizeError {
+                    span: Span { offset: 1, len: 2, line: 1, column: 2 },
+                    message: "Unhandled character".to_owned(),
+                }),
+            ),
+        test_tokenize_number_not_at_offset: (
+                "-é12",
+                Err(TokenizeError {
+                    span: Span { offset: 1, len: 2, line: 1, column: 2 },
+                    message: "Expected digit after `-`".to_owned(),
+                }),
+            ),
+        test_tokenize_number_zero: ("0", Ok(vec![Token::new(TokenType::Number, "0", 0)])),
+        test_tokenize_number_negative_zero_fraction: ("-0.5", Ok(vec![Token::new(TokenType::Number, "-0.5", 0)])),
+        test_tokenize_number_exponent_signs: (
+                "1e+5,2E-5",
+                Ok(vec![
+                    Token::new(TokenType::Number, "1e+5", 0),
+                    Token::new(TokenType::Comma, ",", 4),
+                    Token::new(TokenType::Number, "2E-5", 5),
+                ]),
+            ),
+        test_tokenize_number_followed_by_token: (
+                "[0]",
+                Ok(vec![
+                    Token::new(TokenType::ArrayStart, "[", 0),
+                    Token::new(TokenType::Number, "0", 1),
+                    Token::new(TokenType::ArrayEnd, "]", 2),
+                ]),
+            ),
+        test_tokenize_number_leading_zero: (
+                "01",
+                Err(TokenizeError {
+                    span: Span { offset: 0, len: 1, line: 1, column: 1 },
+                    message: "Leading zeros are not allowed in numbers".to_owned(),
+                }),
+            ),
+        test_tokenize_number_negative_leading_zero: (
+                "-007",
+                Err(TokenizeError {
+                    span: Span { offset: 1, len: 1, line: 1, column: 2 },
+                    message: "Leading zeros are not allowed in numbers".to_owned(),
+                }),
+            ),
+        test_tokenize_number_trailing_point: (
+                "1.",
+                Err(TokenizeError {
+                    span: Span { offset: 2, len: 0, line: 1, column: 3 },
+                    message: "Expected digit after decimal point".to_owned(),
+                }),
+            ),
+        test_tokenize_number_point_before_exponent: (
+                "1.e5",
+                Err(TokenizeError {
+                    span: Span { offset: 2, len: 1, line: 1, column: 3 },
+                    message: "Expected digit after decimal point".to_owned(),
+                }),
+            ),
+        test_tokenize_number_leading_point: (
+                ".5",
+                Err(TokenizeError {
+                    span: Span { offset: 0, len: 1, line: 1, column: 1 },
+                    message: "Expected digit before decimal point".to_owned(),
+                }),
+            ),
+        test_tokenize_number_empty_exponent: (
+                "1e",
+                Err(TokenizeError {
+                    span: Span { offset: 2, len: 0, line: 1, column: 3 },
+                    message: "Expected digit in exponent".to_owned(),
+                }),
+            ),
+        test_tokenize_number_exponent_sign_only: (
+                "1E-]",
+                Err(TokenizeError {
+                    span: Span { offset: 3, len: 1, line: 1, column: 4 },
+                    message: "Expected digit in exponent".to_owned(),
+                }),
+            ),
+        test_tokenize_number_minus_only: (
+                "-",
+                Err(TokenizeError {
+                    span: Span { offset: 1, len: 0, line: 1, column: 2 },
+                    message: "Expected digit after `-`".to_owned(),
+                }),
+            ),
+        test_tokenize_number_minus_letter: (
+                "-x1",
+                Err(TokenizeError {
+                    span: Span { offset: 1, len: 1, line: 1, column: 2 },
+                    message: "Expected digit after `-`".to_owned(),
+                }),
+            ),
         test_tokenize_broken_false: (
                 "foo",
                 Err(TokenizeError {
-                    offset: 0,
+                    span: Span { offset: 0, len: 1, line: 1, column: 1 },
                     message: "Expected literal `false`".to_owned(),
                 }),
             ),
     }
 
+    macro_rules! lenient_tokenizer_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (input, expected) = $value;
+                let options = TokenizeOptions { strict: false };
+                assert_eq!(tokenize_with_options(input, options), expected)
+
+            }
+        )*
+        }
+    }
+
+    lenient_tokenizer_tests! {
+        test_tokenize_lenient_raw_tab: (
+                "\"a\tb\"",
+                Ok(vec![Token::new(TokenType::String, "\"a\tb\"", 0)]),
+            ),
+        test_tokenize_lenient_unknown_escape: (
+                r#""a\xb""#,
+                Ok(vec![Token::new(TokenType::String, r#""a\xb""#, 0)]),
+            ),
+        test_tokenize_lenient_unpaired_surrogate: (
+                r#""\ud83d""#,
+                Err(TokenizeError {
+                    span: Span { offset: 1, len: 6, line: 1, column: 2 },
+                    message: "Unpaired surrogate `\\uD83D`".to_owned(),
+                }),
+            ),
+    }
+
+    #[test]
+    fn test_tokenizer_skip_whitespace() {
+        let mut tokenizer = Tokenizer::new(" \n [ ]", TokenizeOptions::default());
+
+        tokenizer.skip_whitespace();
+        assert_eq!(
+            tokenizer.next(),
+            Some(Ok(Token::new(TokenType::ArrayStart, "[", 3)))
+        );
+        assert_eq!(
+            tokenizer.next(),
+            Some(Ok(Token::new(TokenType::Whitespace, " ", 4)))
+        );
+        tokenizer.skip_whitespace();
+        assert_eq!(
+            tokenizer.next(),
+            Some(Ok(Token::new(TokenType::ArrayEnd, "]", 5)))
+        );
+        assert_eq!(tokenizer.next(), None);
+    }
+
+    #[test]
+    fn test_tokenizer_stops_after_error() {
+        let mut tokenizer = Tokenizer::new("x 1", TokenizeOptions::default());
+
+        assert!(matches!(tokenizer.next(), Some(Err(_))));
+        assert_eq!(tokenizer.next(), None);
+    }
+
+    #[test]
+    fn test_tokenize_large_input() {
+        // Takes minutes if tokenizing is quadratic in the input size
+        let input = format!("[{}1]", "\"some text\", true, -3.5e1, ".repeat(200_000));
+        let tokens = tokenize(&input).unwrap();
+
+        assert_eq!(tokens.len(), 1_800_003);
+        assert_eq!(
+            tokens[1_800_001],
+            Token::new(TokenType::Number, "1", input.len() - 2)
+        );
+    }
+
     #[test]
     fn test_tokenize() {
         let cases: Vec<(&str, Result<Vec<Token>, TokenizeError>)> = vec![];
@@ -281,4 +822,110 @@ mod tests {
             assert_eq!(tokenize(case.0), case.1)
         }
     }
+
+    fn whitespace() -> impl Strategy<Value = &'static str> {
+        prop::sample::select(vec!["", " ", "\n", "\t", "\r\n  "])
+    }
+
+    // A character of string content, written raw or in one of the possible escaped forms
+    fn string_char() -> impl Strategy<Value = String> {
+        prop_oneof![
+            any::<char>().prop_map(|c| {
+                let escaped = to_string(&Json::String(c.to_string()));
+                escaped[1..escaped.len() - 1].to_owned()
+            }),
+            any::<char>().prop_map(|c| {
+                c.encode_utf16(&mut [0; 2])
+                    .iter()
+                    .map(|unit| format!("\\u{:04X}", unit))
+                    .collect()
+            }),
+            prop::sample::select(vec!["\\/", "\\b", "\\f", "\\n", "\\r", "\\t"])
+                .prop_map(|escape| escape.to_owned()),
+        ]
+    }
+
+    fn json_string() -> impl Strategy<Value = String> {
+        prop::collection::vec(string_char(), 0..16)
+            .prop_map(|chars| format!("\"{}\"", chars.concat()))
+    }
+
+    fn json_text() -> impl Strategy<Value = String> {
+        let leaf = prop_oneof![
+            Just("null".to_owned()),
+            Just("true".to_owned()),
+            Just("false".to_owned()),
+            any::<i64>().prop_map(|n| n.to_string()),
+            any::<f64>()
+                .prop_filter("JSON has no NaN or infinity", |f| f.is_finite())
+                .prop_map(|f| format!("{:e}", f)),
+            json_string(),
+        ];
+
+        leaf.prop_recursive(4, 64, 8, |inner| {
+            prop_oneof![
+                prop::collection::vec((whitespace(), inner.clone(), whitespace()), 0..8).prop_map(
+                    |items| {
+                        let items: Vec<String> = items
+                            .into_iter()
+                            .map(|(before, item, after)| format!("{}{}{}", before, item, after))
+                            .collect();
+                        format!("[{}]", items.join(","))
+                    }
+                ),
+                prop::collection::vec((whitespace(), json_string(), whitespace(), inner), 0..8)
+                    .prop_map(|items| {
+                        let items: Vec<String> = items
+                            .into_iter()
+                            .map(|(before, key, after, value)| {
+                                format!("{}{}{}:{}", before, key, after, value)
+                            })
+                            .collect();
+                        format!("{{{}}}", items.join(","))
+                    }),
+            ]
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn test_tokenize_offsets_round_trip(text in json_text()) {
+            let tokens = tokenize(&text).unwrap();
+            let mut expected_offset = 0;
+
+            for token in tokens.iter() {
+                let end = token.offset + token.len();
+                prop_assert_eq!(token.offset, expected_offset);
+                prop_assert_eq!(&text[token.offset..end], token.value);
+                expected_offset += token.len();
+            }
+
+            prop_assert_eq!(expected_offset, text.len());
+        }
+
+        #[test]
+        fn test_tokenize_arbitrary_input(text in any::<String>()) {
+            for token in Tokenizer::new(&text, TokenizeOptions::default()) {
+                match token {
+                    Ok(token) => {
+                        let end = token.offset + token.len();
+                        prop_assert_eq!(&text[token.offset..end], token.value);
+                    }
+                    Err(error) => {
+                        let end = error.span.offset + error.span.len;
+                        prop_assert!(text.is_char_boundary(error.span.offset));
+                        prop_assert!(text.is_char_boundary(end));
+                    }
+                }
+            }
+        }
+
+        #[test]
+        fn test_unescape_round_trip(s in any::<String>()) {
+            let text = to_string(&Json::String(s.clone()));
+            let tokens = tokenize(&text).unwrap();
+
+            prop_assert_eq!(unescape(&text, &tokens[0], true).map(Cow::into_owned), Ok(s));
+        }
+    }
 }
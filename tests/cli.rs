@@ -0,0 +1,106 @@
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+fn run(args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rust_playground"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8(output.stderr.clone()).unwrap()
+}
+
+#[test]
+fn test_cli_bad_file() {
+    let path = env::temp_dir().join(format!("rust_playground_cli_{}.json", std::process::id()));
+    fs::write(&path, "{\n  \"a\" 1\n}\n").unwrap();
+    let path_text = path.to_str().unwrap();
+
+    let output = run(&[".", path_text], "");
+    fs::remove_file(&path).unwrap();
+
+    let expected = [
+        "error: Unexpected token `1` in object".to_owned(),
+        format!(" --> {}:2:7", path_text),
+        "  |".to_owned(),
+        "2 |   \"a\" 1".to_owned(),
+        "  |       ^".to_owned(),
+        "  |".to_owned(),
+        "  = help: add a `:` between the key and its value".to_owned(),
+        "".to_owned(),
+    ];
+
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stderr(&output), expected.join("\n"));
+    assert_eq!(stdout(&output), "");
+}
+
+#[test]
+fn test_cli_missing_file() {
+    let output = run(&[".", "/nonexistent/input.json"], "");
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).starts_with("error: Cannot read /nonexistent/input.json: "));
+}
+
+#[test]
+fn test_cli_bad_stdin() {
+    let output = run(&[], "[1,]");
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains(" --> <stdin>:1:4\n"));
+    assert!(stderr(&output).contains("= help: remove the trailing comma"));
+}
+
+#[test]
+fn test_cli_filter_stdin() {
+    let output = run(&["--compact", ".a[]"], "{\"a\": [1, {\"b\": null}]}");
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "1\n{\"b\":null}\n");
+}
+
+#[test]
+fn test_cli_json_argument() {
+    let output = run(&["--compact", "{\"a\": [1, 2]}"], "");
+    assert_eq!(stdout(&output), "{\"a\":[1,2]}\n");
+
+    let output = run(&["--compact", ".a", "--json={\"a\": true}"], "");
+    assert_eq!(stdout(&output), "true\n");
+}
+
+#[test]
+fn test_cli_argument_neither_filter_nor_json() {
+    let output = run(&["{\"a\" 1}"], "");
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("note: to pass JSON as an argument, use --json=<json>\n"));
+    assert!(stderr(&output).contains("Usage: "));
+}
+
+#[test]
+fn test_cli_unknown_flag() {
+    let output = run(&["--pretty"], "null");
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).starts_with("Usage: "));
+}